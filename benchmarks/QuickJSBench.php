@@ -0,0 +1,69 @@
+<?php
+
+declare(strict_types=1);
+
+namespace Shopware\PHPExtension\Benchmarks;
+
+use PhpBench\Attributes as Bench;
+use Shopware\PHPExtension\QuickJS\QuickJS;
+
+/**
+ * Compares passing a product listing through Zval conversion and through JSON
+ */
+#[Bench\BeforeMethods('setUp')]
+#[Bench\Revs(20)]
+#[Bench\Iterations(5)]
+class QuickJSBench
+{
+    private QuickJS $js;
+
+    /** @var list<array<string, mixed>> */
+    private array $products;
+
+    private string $productsJson;
+
+    public function setUp(): void
+    {
+        $this->js = new QuickJS();
+        $this->js->eval('function render(products) { return products.map((p) => ({ id: p.id, label: p.name + " " + p.price.gross })); }');
+
+        $this->products = [];
+        for ($i = 0; $i < 1000; ++$i) {
+            $this->products[] = [
+                'id' => bin2hex(random_bytes(16)),
+                'name' => 'Product ' . $i,
+                'active' => $i % 3 !== 0,
+                'price' => ['net' => $i * 8.4, 'gross' => $i * 10.0, 'currency' => 'EUR'],
+                'tags' => ['new', 'sale', 'category-' . ($i % 10)],
+            ];
+        }
+        $this->productsJson = json_encode($this->products, JSON_THROW_ON_ERROR);
+    }
+
+    public function benchSetGlobal(): void
+    {
+        $this->js->setGlobal('products', $this->products);
+    }
+
+    public function benchSetGlobalJson(): void
+    {
+        $this->js->setGlobalJson('products', json_encode($this->products, JSON_THROW_ON_ERROR));
+    }
+
+    public function benchSetGlobalJsonPreEncoded(): void
+    {
+        $this->js->setGlobalJson('products', $this->productsJson);
+    }
+
+    public function benchRoundTrip(): void
+    {
+        $this->js->setGlobal('products', $this->products);
+        $this->js->eval('render(products)');
+    }
+
+    public function benchRoundTripJson(): void
+    {
+        $this->js->setGlobalJson('products', json_encode($this->products, JSON_THROW_ON_ERROR));
+        json_decode($this->js->evalJson('render(products)'), true, 512, JSON_THROW_ON_ERROR);
+    }
+}
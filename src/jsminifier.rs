@@ -0,0 +1,145 @@
+use ext_php_rs::prelude::*;
+use ext_php_rs::types::{ZendHashTable, Zval};
+use oxc::allocator::Allocator;
+use oxc::codegen::{Codegen, CodegenOptions, CommentOptions, LegalComment};
+use oxc::minifier::{CompressOptions, MangleOptions, Minifier, MinifierOptions};
+use oxc::parser::Parser;
+use oxc::span::SourceType;
+use oxc_compat::EngineTargets;
+use std::path::PathBuf;
+
+use crate::options::{option_bool, option_str};
+
+/// JsMinifier - A fast JavaScript minifier
+///
+/// Example usage:
+/// ```php
+/// $minifier = new JsMinifier();
+/// $result = $minifier->minify('function add(first, second) { return first + second; }');
+/// ```
+#[php_class]
+#[php(name = "Shopware\\PHPExtension\\JsMinifier")]
+pub struct JsMinifier;
+
+#[php_impl]
+impl JsMinifier {
+    /// Create a new JsMinifier instance
+    pub fn __construct() -> Self {
+        JsMinifier
+    }
+
+    /// Minify JavaScript code
+    ///
+    /// Supported options:
+    /// - `mangle` (bool, default true): shorten local variable and function names
+    /// - `top_level` (bool, default false): also mangle top level names
+    /// - `compress` (bool, default true): apply compressing transformations
+    /// - `drop_console` (bool, default false): remove `console.*` calls when compressing
+    /// - `drop_debugger` (bool, default true): remove `debugger` statements when compressing
+    /// - `target` (string, default `esnext`): newest syntax the compressor may introduce,
+    ///   e.g. `es2015` or `chrome80,safari14`. This only limits the compressor, the input
+    ///   is not transpiled: code already using newer syntax such as `??` keeps it
+    /// - `module` (bool, default false): parse the code as an ES module instead of a script
+    /// - `license_comments` (string, default `inline`): `inline` keeps `/*! ... */` and
+    ///   `@license` comments in place, `eof` moves them to the end, `none` drops them
+    /// - `source_map` (bool, default false): return `['code' => ..., 'map' => ...]`
+    ///   including a JSON source map instead of the code only
+    /// - `filename` (string, default `input.js`): source name used in the source map
+    ///
+    /// Example:
+    /// ```php
+    /// $result = $minifier->minify($code, ['target' => 'es2017', 'source_map' => true]);
+    /// file_put_contents('plugin.min.js.map', $result['map']);
+    /// ```
+    #[php(optional = options)]
+    pub fn minify(&self, code: &str, options: Option<&ZendHashTable>) -> PhpResult<Zval> {
+        let mangle = option_bool(options, "mangle", true);
+        let top_level = option_bool(options, "top_level", false);
+        let compress = option_bool(options, "compress", true);
+        let drop_console = option_bool(options, "drop_console", false);
+        let drop_debugger = option_bool(options, "drop_debugger", true);
+        let module = option_bool(options, "module", false);
+        let source_map = option_bool(options, "source_map", false);
+        let filename = option_str(options, "filename").unwrap_or_else(|| "input.js".to_string());
+
+        let target = match option_str(options, "target") {
+            Some(target) => EngineTargets::from_target(&target)
+                .map_err(|e| PhpException::default(format!("Invalid target: {}", e)))?,
+            None => EngineTargets::default(),
+        };
+
+        let legal = match option_str(options, "license_comments").as_deref() {
+            None | Some("inline") => LegalComment::Inline,
+            Some("eof") => LegalComment::Eof,
+            Some("none") => LegalComment::None,
+            Some(other) => {
+                return Err(PhpException::default(format!(
+                    "Unknown license_comments mode '{}', expected inline, eof or none",
+                    other
+                )));
+            }
+        };
+
+        let allocator = Allocator::default();
+        let source_type = SourceType::default().with_module(module);
+        let parsed = Parser::new(&allocator, code, source_type).parse();
+
+        if !parsed.errors.is_empty() {
+            let messages: Vec<String> = parsed.errors.iter().map(|e| e.to_string()).collect();
+            return Err(PhpException::default(format!(
+                "JavaScript parse error: {}",
+                messages.join("; ")
+            )));
+        }
+
+        let mut program = parsed.program;
+
+        let minifier_options = MinifierOptions {
+            mangle: mangle.then(|| MangleOptions {
+                top_level,
+                ..MangleOptions::default()
+            }),
+            compress: compress.then(|| CompressOptions {
+                target,
+                drop_console,
+                drop_debugger,
+                ..CompressOptions::default()
+            }),
+        };
+        let minified = Minifier::new(minifier_options).minify(&allocator, &mut program);
+
+        let codegen_options = CodegenOptions {
+            minify: true,
+            comments: CommentOptions {
+                normal: false,
+                jsdoc: false,
+                annotation: false,
+                legal,
+            },
+            source_map_path: source_map.then(|| PathBuf::from(&filename)),
+            ..CodegenOptions::default()
+        };
+
+        let output = Codegen::new()
+            .with_options(codegen_options)
+            .with_scoping(minified.scoping)
+            .build(&program);
+
+        let mut zval = Zval::new();
+        if source_map {
+            let map = output.map.map(|map| map.to_json_string()).unwrap_or_default();
+
+            let mut arr = ZendHashTable::new();
+            arr.insert("code", output.code)
+                .map_err(|e| PhpException::default(format!("Failed to build result: {:?}", e)))?;
+            arr.insert("map", map)
+                .map_err(|e| PhpException::default(format!("Failed to build result: {:?}", e)))?;
+            zval.set_hashtable(arr);
+        } else {
+            zval.set_string(&output.code, false)
+                .map_err(|e| PhpException::default(format!("Failed to set string: {:?}", e)))?;
+        }
+
+        Ok(zval)
+    }
+}
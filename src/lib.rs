@@ -4,10 +4,15 @@ use ext_php_rs::prelude::*;
 
 mod quickjs;
 mod lightningcss;
+mod scss;
+mod jsminifier;
+mod options;
 
 
-pub use quickjs::{QuickJS, QuickObject};
+pub use quickjs::{QuickBinary, QuickClass, QuickJS, QuickJSContext, QuickJSRuntime, QuickObject};
 pub use lightningcss::LightningCSS;
+pub use scss::ScssCompiler;
+pub use jsminifier::JsMinifier;
 
 
 #[php_module]
@@ -15,6 +20,12 @@ pub fn get_module(module: ModuleBuilder) -> ModuleBuilder {
     module
         .class::<QuickJS>()
         .class::<QuickObject>()
+        .class::<QuickBinary>()
+        .class::<QuickClass>()
+        .class::<QuickJSRuntime>()
+        .class::<QuickJSContext>()
         .class::<LightningCSS>()
+        .class::<ScssCompiler>()
+        .class::<JsMinifier>()
 
 }
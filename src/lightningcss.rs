@@ -1,7 +1,22 @@
 use ext_php_rs::prelude::*;
-use ext_php_rs::types::Zval;
+use ext_php_rs::types::{ArrayKey, ZendHashTable, Zval};
 use lightningcss::stylesheet::{MinifyOptions, ParserOptions, PrinterOptions, StyleSheet};
 use lightningcss::targets::{Browsers, Targets};
+use lightningcss::values::string::CowArcStr;
+use lru::LruCache;
+use rayon::prelude::*;
+use sha2::{Digest, Sha256};
+use std::num::NonZeroUsize;
+use std::sync::{LazyLock, Mutex};
+
+use crate::options::{option_bool, option_long};
+
+/// Number of compiled stylesheets kept when the cache is enabled without a capacity
+const DEFAULT_CACHE_CAPACITY: NonZeroUsize = NonZeroUsize::new(256).unwrap();
+
+/// Process-wide cache of compiled CSS, shared by all instances that enabled caching
+static CACHE: LazyLock<Mutex<CssCache>> =
+    LazyLock::new(|| Mutex::new(CssCache::new(DEFAULT_CACHE_CAPACITY)));
 
 /// LightningCSS - A fast CSS parser, transformer, and minifier
 ///
@@ -14,6 +29,7 @@ use lightningcss::targets::{Browsers, Targets};
 #[php(name = "Shopware\\PHPExtension\\LightningCSS\\LightningCSS")]
 pub struct LightningCSS {
     targets: Targets,
+    cache: bool,
 }
 
 #[php_impl]
@@ -22,6 +38,7 @@ impl LightningCSS {
     pub fn __construct() -> Self {
         LightningCSS { 
             targets: Targets::default(),
+            cache: false,
         }
     }
 
@@ -47,7 +64,7 @@ impl LightningCSS {
 
             let version = value.long().unwrap_or(0) as u32;
             // Convert version number to lightningcss format (major << 16 | minor << 8 | patch)
-            let version_bits = (version << 16) | (0 << 8) | 0;
+            let version_bits = version << 16;
 
             match browser_name.as_str() {
                 "chrome" => browser_targets.chrome = Some(version_bits),
@@ -72,69 +89,155 @@ impl LightningCSS {
 
     /// Minify CSS code
     ///
+    /// Supported options:
+    /// - `license_comments` (bool, default true): keep `/*! ... */` license comments
+    ///   at the start of the stylesheet
+    /// - `hoist_license_comments` (bool, default false): collect license comments from
+    ///   the whole stylesheet and move them to the top of the output
+    ///
+    /// Example:
+    /// ```php
+    /// $css->minify($bundle, ['hoist_license_comments' => true]);
+    /// ```
+    ///
     /// Returns minified CSS string
-    pub fn minify(&self, css: &str) -> PhpResult<String> {
-        let mut stylesheet = StyleSheet::parse(css, ParserOptions::default())
-            .map_err(|e| PhpException::default(format!("CSS parse error: {:?}", e)))?;
+    #[php(optional = options)]
+    pub fn minify(&self, css: &str, options: Option<&ZendHashTable>) -> PhpResult<String> {
+        let comments = LicenseComments::from_options(options);
+        let key = cache_key("minify", css, &self.targets, &comments);
 
-        let minify_options = MinifyOptions {
-            targets: self.targets.clone(),
-            ..Default::default()
-        };
+        cached(self.cache, key, || minify_css(css, &self.targets, comments))
+            .map_err(PhpException::default)
+    }
 
-        stylesheet
-            .minify(minify_options)
-            .map_err(|e| PhpException::default(format!("CSS minify error: {:?}", e)))?;
+    /// Minify a batch of independent stylesheets in parallel
+    ///
+    /// The stylesheets are compiled on a thread pool; keys of the input array are
+    /// preserved and every entry reports either the minified code or its error, so a
+    /// broken stylesheet does not abort the whole batch. Accepts the same options as
+    /// `minify`.
+    ///
+    /// Example:
+    /// ```php
+    /// $results = $css->minifyMany([
+    ///     'storefront' => $storefrontCss,
+    ///     'b2b' => $b2bCss,
+    /// ]);
+    /// // ['storefront' => ['code' => '...', 'error' => null], 'b2b' => [...]]
+    /// ```
+    #[php(optional = options)]
+    pub fn minify_many(&self, files: &ZendHashTable, options: Option<&ZendHashTable>) -> PhpResult<Zval> {
+        let comments = LicenseComments::from_options(options);
 
-        let printer_options = PrinterOptions {
-            minify: true,
-            targets: self.targets.clone(),
-            ..Default::default()
-        };
+        // Copy everything out of PHP memory first, the worker threads must not touch Zvals
+        let mut keys: Vec<ArrayKey<'static>> = Vec::with_capacity(files.len());
+        let mut sources: Vec<Option<String>> = Vec::with_capacity(files.len());
+        for (key, value) in files.iter() {
+            keys.push(match key {
+                ArrayKey::Long(idx) => ArrayKey::Long(idx),
+                ArrayKey::String(s) => ArrayKey::String(s),
+                ArrayKey::Str(s) => ArrayKey::String(s.to_string()),
+            });
+            sources.push(value.string());
+        }
 
-        let result = stylesheet
-            .to_css(printer_options)
-            .map_err(|e| PhpException::default(format!("CSS print error: {:?}", e)))?;
+        let targets = self.targets;
+        let use_cache = self.cache;
+        let results: Vec<Result<String, String>> = sources
+            .par_iter()
+            .map(|source| match source {
+                Some(css) => {
+                    let key = cache_key("minify", css, &targets, &comments);
+                    cached(use_cache, key, || minify_css(css, &targets, comments))
+                }
+                None => Err("Expected a CSS string".to_string()),
+            })
+            .collect();
 
-        Ok(result.code)
+        let mut arr = ZendHashTable::new();
+        for (key, result) in keys.into_iter().zip(results) {
+            let mut entry = ZendHashTable::new();
+            let (code, error) = match result {
+                Ok(code) => (Some(code), None),
+                Err(error) => (None, Some(error)),
+            };
+            entry
+                .insert("code", code)
+                .map_err(|e| PhpException::default(format!("Failed to build result: {:?}", e)))?;
+            entry
+                .insert("error", error)
+                .map_err(|e| PhpException::default(format!("Failed to build result: {:?}", e)))?;
+            arr.insert(key, entry)
+                .map_err(|e| PhpException::default(format!("Failed to build result: {:?}", e)))?;
+        }
+
+        let mut zval = Zval::new();
+        zval.set_hashtable(arr);
+        Ok(zval)
     }
 
     /// Transform CSS for browser compatibility without minification
     ///
     /// Adds vendor prefixes and transforms modern syntax for older browsers
     pub fn transform(&self, css: &str) -> PhpResult<String> {
+        let key = cache_key("transform", css, &self.targets, &());
+
+        cached(self.cache, key, || transform_css(css, &self.targets))
+            .map_err(PhpException::default)
+    }
+
+    /// Parse and pretty-print CSS (formats the CSS)
+    ///
+    /// Supported options:
+    /// - `targets` (bool, default false): apply the configured browser targets,
+    ///   producing the same rules as `transform`
+    /// - `indent` (int, default 2): number of spaces per indentation level
+    /// - `comments` (bool, default true): keep leading `/*! ... */` license comments
+    ///
+    /// Example:
+    /// ```php
+    /// $css->format('body{color:red}', ['targets' => true, 'indent' => 4]);
+    /// ```
+    #[php(optional = options)]
+    pub fn format(&self, css: &str, options: Option<&ZendHashTable>) -> PhpResult<String> {
+        let apply_targets = option_bool(options, "targets", false);
+        let indent = option_long(options, "indent", 2);
+        let keep_comments = option_bool(options, "comments", true);
+
+        if !(0..=16).contains(&indent) {
+            return Err(PhpException::default(format!(
+                "Invalid indent width {}, expected a value between 0 and 16",
+                indent
+            )));
+        }
+
         let mut stylesheet = StyleSheet::parse(css, ParserOptions::default())
             .map_err(|e| PhpException::default(format!("CSS parse error: {:?}", e)))?;
 
-        let minify_options = MinifyOptions {
-            targets: self.targets.clone(),
-            ..Default::default()
-        };
-
-        stylesheet
-            .minify(minify_options)
-            .map_err(|e| PhpException::default(format!("CSS transform error: {:?}", e)))?;
+        if !keep_comments {
+            stylesheet.license_comments.clear();
+        }
 
-        let printer_options = PrinterOptions {
-            minify: false,
-            targets: self.targets.clone(),
-            ..Default::default()
+        let targets = if apply_targets {
+            self.targets
+        } else {
+            Targets::default()
         };
 
-        let result = stylesheet
-            .to_css(printer_options)
-            .map_err(|e| PhpException::default(format!("CSS print error: {:?}", e)))?;
-
-        Ok(result.code)
-    }
+        if apply_targets {
+            let minify_options = MinifyOptions {
+                targets,
+                ..Default::default()
+            };
 
-    /// Parse and pretty-print CSS (formats the CSS)
-    pub fn format(&self, css: &str) -> PhpResult<String> {
-        let stylesheet = StyleSheet::parse(css, ParserOptions::default())
-            .map_err(|e| PhpException::default(format!("CSS parse error: {:?}", e)))?;
+            stylesheet
+                .minify(minify_options)
+                .map_err(|e| PhpException::default(format!("CSS transform error: {:?}", e)))?;
+        }
 
         let printer_options = PrinterOptions {
             minify: false,
+            targets,
             ..Default::default()
         };
 
@@ -142,14 +245,26 @@ impl LightningCSS {
             .to_css(printer_options)
             .map_err(|e| PhpException::default(format!("CSS print error: {:?}", e)))?;
 
-        Ok(result.code)
+        Ok(reindent(&result.code, indent as usize))
     }
 
     /// Validate CSS syntax
     ///
+    /// The configured browser targets are applied as well, so CSS that parses
+    /// but cannot be compiled for the targets is reported as invalid.
+    ///
     /// Returns true if CSS is valid, throws exception with details if invalid
     pub fn validate(&self, css: &str) -> PhpResult<bool> {
-        StyleSheet::parse(css, ParserOptions::default())
+        let mut stylesheet = StyleSheet::parse(css, ParserOptions::default())
+            .map_err(|e| PhpException::default(format!("CSS validation error: {:?}", e)))?;
+
+        let minify_options = MinifyOptions {
+            targets: self.targets,
+            ..Default::default()
+        };
+
+        stylesheet
+            .minify(minify_options)
             .map_err(|e| PhpException::default(format!("CSS validation error: {:?}", e)))?;
 
         Ok(true)
@@ -169,4 +284,337 @@ impl LightningCSS {
         zval.set_hashtable(arr);
         Ok(zval)
     }
+
+    /// Enable the process-wide cache of compiled CSS for this instance
+    ///
+    /// Results of `minify`, `minifyMany` and `transform` are cached by a SHA-256
+    /// digest of the source, the browser targets and the options. The cache is
+    /// shared by all instances in the process, see `setCacheCapacity()`.
+    ///
+    /// Example:
+    /// ```php
+    /// $css->enableCache();
+    /// ```
+    pub fn enable_cache(&mut self) {
+        self.cache = true;
+    }
+
+    /// Stop using the cache for this instance, cached entries are kept for other instances
+    pub fn disable_cache(&mut self) {
+        self.cache = false;
+    }
+
+    /// Set how many compiled stylesheets the process-wide cache keeps
+    ///
+    /// Applies to all instances, least recently used entries beyond the capacity are
+    /// evicted right away.
+    ///
+    /// Example:
+    /// ```php
+    /// LightningCSS::setCacheCapacity(512);
+    /// ```
+    pub fn set_cache_capacity(capacity: i64) -> PhpResult<()> {
+        let capacity = usize::try_from(capacity)
+            .ok()
+            .and_then(NonZeroUsize::new)
+            .ok_or_else(|| {
+                PhpException::default(format!(
+                    "Invalid cache capacity {}, expected a positive number",
+                    capacity
+                ))
+            })?;
+
+        lock_cache().set_capacity(capacity);
+        Ok(())
+    }
+
+    /// Remove all cached entries of the process-wide cache and reset the statistics
+    pub fn clear_cache() {
+        lock_cache().clear();
+    }
+
+    /// Get statistics of the process-wide cache
+    ///
+    /// Returns an array with `hits`, `misses`, `evictions`, `entries` and `capacity`
+    pub fn get_cache_stats() -> PhpResult<Zval> {
+        let cache = lock_cache();
+        let stats = [
+            ("hits", cache.hits),
+            ("misses", cache.misses),
+            ("evictions", cache.evictions),
+            ("entries", cache.entries.len() as u64),
+            ("capacity", cache.entries.cap().get() as u64),
+        ];
+
+        let mut arr = ZendHashTable::new();
+        for (key, value) in stats {
+            arr.insert(key, value as i64)
+                .map_err(|e| PhpException::default(format!("Failed to build cache stats: {:?}", e)))?;
+        }
+
+        let mut zval = Zval::new();
+        zval.set_hashtable(arr);
+        Ok(zval)
+    }
+}
+
+/// How `/*! ... */` license comments are treated when minifying
+#[derive(Clone, Copy, Debug)]
+struct LicenseComments {
+    keep: bool,
+    hoist: bool,
+}
+
+impl LicenseComments {
+    fn from_options(options: Option<&ZendHashTable>) -> Self {
+        LicenseComments {
+            keep: option_bool(options, "license_comments", true),
+            hoist: option_bool(options, "hoist_license_comments", false),
+        }
+    }
+}
+
+/// Minify a stylesheet without touching any PHP state, so it can run on worker threads
+fn minify_css(css: &str, targets: &Targets, comments: LicenseComments) -> Result<String, String> {
+    let mut stylesheet = StyleSheet::parse(css, ParserOptions::default())
+        .map_err(|e| format!("CSS parse error: {:?}", e))?;
+
+    if !comments.keep {
+        stylesheet.license_comments.clear();
+    } else if comments.hoist {
+        stylesheet.license_comments = collect_license_comments(css)
+            .into_iter()
+            .map(CowArcStr::from)
+            .collect();
+    }
+
+    let minify_options = MinifyOptions {
+        targets: *targets,
+        ..Default::default()
+    };
+
+    stylesheet
+        .minify(minify_options)
+        .map_err(|e| format!("CSS minify error: {:?}", e))?;
+
+    let printer_options = PrinterOptions {
+        minify: true,
+        targets: *targets,
+        ..Default::default()
+    };
+
+    let result = stylesheet
+        .to_css(printer_options)
+        .map_err(|e| format!("CSS print error: {:?}", e))?;
+
+    Ok(result.code)
+}
+
+/// Transform a stylesheet for the targets without minifying it
+fn transform_css(css: &str, targets: &Targets) -> Result<String, String> {
+    let mut stylesheet = StyleSheet::parse(css, ParserOptions::default())
+        .map_err(|e| format!("CSS parse error: {:?}", e))?;
+
+    let minify_options = MinifyOptions {
+        targets: *targets,
+        ..Default::default()
+    };
+
+    stylesheet
+        .minify(minify_options)
+        .map_err(|e| format!("CSS transform error: {:?}", e))?;
+
+    let printer_options = PrinterOptions {
+        minify: false,
+        targets: *targets,
+        ..Default::default()
+    };
+
+    let result = stylesheet
+        .to_css(printer_options)
+        .map_err(|e| format!("CSS print error: {:?}", e))?;
+
+    Ok(result.code)
+}
+
+/// SHA-256 digest of the operation, source, targets and options of a compilation
+type CacheKey = [u8; 32];
+
+/// A bounded least-recently-used cache of compiled CSS, keyed by `cache_key`
+struct CssCache {
+    entries: LruCache<CacheKey, String>,
+    hits: u64,
+    misses: u64,
+    evictions: u64,
+}
+
+impl CssCache {
+    fn new(capacity: NonZeroUsize) -> Self {
+        CssCache {
+            entries: LruCache::new(capacity),
+            hits: 0,
+            misses: 0,
+            evictions: 0,
+        }
+    }
+
+    fn get(&mut self, key: &CacheKey) -> Option<String> {
+        match self.entries.get(key) {
+            Some(code) => {
+                self.hits += 1;
+                Some(code.clone())
+            }
+            None => {
+                self.misses += 1;
+                None
+            }
+        }
+    }
+
+    fn insert(&mut self, key: CacheKey, code: String) {
+        // `push` also returns the old entry when `key` was already cached
+        if let Some((evicted, _)) = self.entries.push(key, code)
+            && evicted != key
+        {
+            self.evictions += 1;
+        }
+    }
+
+    fn set_capacity(&mut self, capacity: NonZeroUsize) {
+        let before = self.entries.len();
+        self.entries.resize(capacity);
+        self.evictions += (before - self.entries.len()) as u64;
+    }
+
+    fn clear(&mut self) {
+        *self = CssCache::new(self.entries.cap());
+    }
+}
+
+fn lock_cache() -> std::sync::MutexGuard<'static, CssCache> {
+    // A panic while holding the lock cannot leave the cache inconsistent, keep using it
+    CACHE.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+/// Digest the operation, source, targets and options into a cache key
+///
+/// Every part is prefixed with its length, so no two different inputs share the
+/// digested bytes.
+fn cache_key(operation: &str, css: &str, targets: &Targets, options: &impl std::fmt::Debug) -> CacheKey {
+    let targets = format!("{:?}", targets);
+    let options = format!("{:?}", options);
+
+    let mut hasher = Sha256::new();
+    for part in [operation, css, &targets, &options] {
+        hasher.update((part.len() as u64).to_le_bytes());
+        hasher.update(part.as_bytes());
+    }
+    hasher.finalize().into()
+}
+
+/// Look up `key` in the cache when enabled, computing and storing the result on a miss
+///
+/// Errors are not cached so fixed stylesheets are recompiled on the next call.
+fn cached(enabled: bool, key: CacheKey, compute: impl FnOnce() -> Result<String, String>) -> Result<String, String> {
+    if !enabled {
+        return compute();
+    }
+
+    if let Some(code) = lock_cache().get(&key) {
+        return Ok(code);
+    }
+
+    let code = compute()?;
+    lock_cache().insert(key, code.clone());
+    Ok(code)
+}
+
+/// Collect the contents of all `/*! ... */` comments in source order, skipping duplicates
+///
+/// The parser only keeps license comments that precede the first rule, so comments
+/// further down (e.g. from concatenated vendor stylesheets) have to be found here.
+fn collect_license_comments(css: &str) -> Vec<String> {
+    let mut comments: Vec<String> = Vec::new();
+    let bytes = css.as_bytes();
+    let mut i = 0;
+
+    while i < bytes.len() {
+        match bytes[i] {
+            quote @ (b'"' | b'\'') => {
+                i += 1;
+                while i < bytes.len() && bytes[i] != quote {
+                    if bytes[i] == b'\\' {
+                        i += 1;
+                    }
+                    i += 1;
+                }
+                i += 1;
+            }
+            b'/' if bytes.get(i + 1) == Some(&b'*') => {
+                let start = i + 2;
+                let end = css[start..].find("*/").map(|pos| start + pos).unwrap_or(css.len());
+                let comment = &css[start..end];
+                if comment.starts_with('!') && !comments.iter().any(|c| c == comment) {
+                    comments.push(comment.to_string());
+                }
+                i = end + 2;
+            }
+            _ => i += 1,
+        }
+    }
+
+    comments
+}
+
+/// Replace the printer's fixed two-space indentation with `width` spaces per level
+///
+/// The lightningcss printer always indents by two spaces and has no setting for it,
+/// so the printed code is rewritten. Only the indentation of the current block depth
+/// is replaced: alignment beyond it is kept, and lines that start inside a comment or
+/// a string are copied unchanged.
+fn reindent(code: &str, width: usize) -> String {
+    if width == 2 {
+        return code.to_string();
+    }
+
+    let mut out = String::with_capacity(code.len());
+    let mut depth = 0usize;
+    let mut in_comment = false;
+    let mut quote: Option<char> = None;
+
+    for line in code.split_inclusive('\n') {
+        if in_comment || quote.is_some() {
+            out.push_str(line);
+        } else {
+            let content = line.trim_start_matches(' ');
+            let level = if content.starts_with('}') { depth.saturating_sub(1) } else { depth };
+            let indent = (line.len() - content.len()).min(level * 2);
+            out.push_str(&" ".repeat(indent / 2 * width + indent % 2));
+            out.push_str(&line[indent..]);
+        }
+
+        let mut chars = line.chars().peekable();
+        while let Some(c) = chars.next() {
+            if in_comment {
+                if c == '*' && chars.next_if_eq(&'/').is_some() {
+                    in_comment = false;
+                }
+            } else if let Some(q) = quote {
+                if c == '\\' {
+                    chars.next();
+                } else if c == q || c == '\n' {
+                    quote = None;
+                }
+            } else {
+                match c {
+                    '/' if chars.next_if_eq(&'*').is_some() => in_comment = true,
+                    '"' | '\'' => quote = Some(c),
+                    '{' => depth += 1,
+                    '}' => depth = depth.saturating_sub(1),
+                    _ => {}
+                }
+            }
+        }
+    }
+    out
 }
@@ -0,0 +1,26 @@
+//! Helpers for reading PHP options arrays passed to the extension classes
+
+use ext_php_rs::types::ZendHashTable;
+
+/// Read a boolean entry from an options array, falling back to `default`
+pub(crate) fn option_bool(options: Option<&ZendHashTable>, key: &str, default: bool) -> bool {
+    options
+        .and_then(|opts| opts.get(key))
+        .and_then(|val| val.bool())
+        .unwrap_or(default)
+}
+
+/// Read an integer entry from an options array, falling back to `default`
+pub(crate) fn option_long(options: Option<&ZendHashTable>, key: &str, default: i64) -> i64 {
+    options
+        .and_then(|opts| opts.get(key))
+        .and_then(|val| val.long())
+        .unwrap_or(default)
+}
+
+/// Read a string entry from an options array
+pub(crate) fn option_str(options: Option<&ZendHashTable>, key: &str) -> Option<String> {
+    options
+        .and_then(|opts| opts.get(key))
+        .and_then(|val| val.string())
+}
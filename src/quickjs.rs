@@ -1,12 +1,181 @@
-use ext_php_rs::convert::IntoZvalDyn;
+use ext_php_rs::convert::{FromZval, IntoZval, IntoZvalDyn};
 use ext_php_rs::prelude::*;
-use ext_php_rs::types::{ArrayKey, ZendCallable, Zval};
-use rquickjs::{Array, Context, Function as JsFunction, Object, Runtime, Value};
-use std::cell::RefCell;
+use ext_php_rs::types::{ArrayKey, ZendCallable, ZendHashTable, ZendObject, Zval};
+use ext_php_rs::zend::ClassEntry;
+use rquickjs::context::{Intrinsic, intrinsic};
+use rquickjs::function::Constructor;
+use rquickjs::object::{Accessor, AsProperty, Property};
+use rquickjs::{
+    qjs, Array, Coerced, Context, Exception, Filter, Function as JsFunction, Object, Runtime, Type, TypedArray,
+    Value,
+};
+use std::cell::{Cell, RefCell};
+use std::rc::Rc;
 use std::sync::Arc;
 
+use crate::options::{option_bool, option_long, option_str};
+
+mod class;
+mod console;
+mod live;
+mod runtime;
+mod timers;
+
+pub use class::QuickClass;
+use class::{backing_object, context_register_class, wrap_instance, QuickClassData};
+use console::Console;
+use live::{context_register_live_object, live_object};
+pub use runtime::{QuickJSContext, QuickJSRuntime};
+use timers::Timers;
+
+/// Default nesting limit of converted values, the same as `json_encode()`
+const DEFAULT_MAX_DEPTH: usize = 512;
+
+/// Replacement of circular references in `marker` mode
+const CIRCULAR_MARKER: &str = "[Circular]";
+
+/// How values are converted between PHP and JavaScript
+///
+/// Stored as runtime userdata, so conversions inside PHP callbacks see them too.
+#[derive(Clone, Copy)]
+struct ConversionOptions {
+    max_depth: usize,
+    circular_marker: bool,
+    empty_array_as_object: bool,
+    objects_as_stdclass: bool,
+}
+
+unsafe impl<'js> rquickjs::JsLifetime<'js> for ConversionOptions {
+    type Changed<'to> = ConversionOptions;
+}
+
+impl Default for ConversionOptions {
+    fn default() -> Self {
+        ConversionOptions {
+            max_depth: DEFAULT_MAX_DEPTH,
+            circular_marker: false,
+            empty_array_as_object: false,
+            objects_as_stdclass: false,
+        }
+    }
+}
+
+impl ConversionOptions {
+    /// Read the `max_depth`, `circular`, `empty_array` and `objects` options
+    fn from_options(options: Option<&ZendHashTable>) -> PhpResult<Self> {
+        let max_depth = option_long(options, "max_depth", DEFAULT_MAX_DEPTH as i64);
+        if max_depth < 1 {
+            return Err(PhpException::default("max_depth must be at least 1".to_string()));
+        }
+
+        let circular_marker = match option_str(options, "circular").as_deref() {
+            None | Some("throw") => false,
+            Some("marker") => true,
+            Some(other) => {
+                return Err(PhpException::default(format!(
+                    "Unknown circular mode '{}', expected throw or marker",
+                    other
+                )));
+            }
+        };
+
+        let empty_array_as_object = match option_str(options, "empty_array").as_deref() {
+            None | Some("array") => false,
+            Some("object") => true,
+            Some(other) => {
+                return Err(PhpException::default(format!(
+                    "Unknown empty_array mode '{}', expected array or object",
+                    other
+                )));
+            }
+        };
+
+        let objects_as_stdclass = match option_str(options, "objects").as_deref() {
+            None | Some("array") => false,
+            Some("stdclass") => true,
+            Some(other) => {
+                return Err(PhpException::default(format!(
+                    "Unknown objects mode '{}', expected array or stdclass",
+                    other
+                )));
+            }
+        };
+
+        Ok(ConversionOptions {
+            max_depth: max_depth as usize,
+            circular_marker,
+            empty_array_as_object,
+            objects_as_stdclass,
+        })
+    }
+
+    /// Apply the options to all contexts of the runtime `context` belongs to
+    fn install(self, context: &Context) -> PhpResult<()> {
+        context.with(|ctx| {
+            ctx.store_userdata(self)
+                .map(|_| ())
+                .map_err(|e| PhpException::default(format!("Failed to store conversion options: {:?}", e)))
+        })
+    }
+
+    fn of(ctx: &rquickjs::Ctx<'_>) -> Self {
+        ctx.userdata::<ConversionOptions>().map(|options| *options).unwrap_or_default()
+    }
+}
+
+/// Arrays and objects currently being converted, from the outermost to the innermost
+///
+/// Only ancestors are tracked, so a value shared by two siblings is converted
+/// twice instead of being reported as circular.
+struct ConversionPath<T> {
+    options: ConversionOptions,
+    containers: Vec<T>,
+}
+
+impl<T: PartialEq> ConversionPath<T> {
+    fn new(options: ConversionOptions) -> Self {
+        ConversionPath {
+            options,
+            containers: Vec::new(),
+        }
+    }
+
+    /// Descend into `container`, `false` when it is circular and should become the marker
+    fn enter(&mut self, container: T) -> Result<bool, String> {
+        if self.containers.contains(&container) {
+            return if self.options.circular_marker {
+                Ok(false)
+            } else {
+                Err(format!("Circular reference detected at depth {}", self.containers.len()))
+            };
+        }
+        if self.containers.len() >= self.options.max_depth {
+            return Err(format!("Maximum nesting depth of {} exceeded", self.options.max_depth));
+        }
+
+        self.containers.push(container);
+        Ok(true)
+    }
+
+    fn leave(&mut self) {
+        self.containers.pop();
+    }
+}
+
 /// Converts a PHP Zval to a QuickJS Value
 fn zval_to_js<'js>(ctx: &rquickjs::Ctx<'js>, zval: &Zval) -> rquickjs::Result<Value<'js>> {
+    let mut path = ConversionPath::new(ConversionOptions::of(ctx));
+    convert_zval(ctx, zval, &mut path)
+}
+
+fn convert_zval<'js>(
+    ctx: &rquickjs::Ctx<'js>,
+    zval: &Zval,
+    path: &mut ConversionPath<usize>,
+) -> rquickjs::Result<Value<'js>> {
+    // Elements assigned by reference (`$a[] = &$b`) are wrapped in a reference
+    let zval = zval.reference().unwrap_or(zval);
+
     if zval.is_null() {
         Ok(Value::new_null(ctx.clone()))
     } else if zval.is_bool() {
@@ -14,7 +183,11 @@ fn zval_to_js<'js>(ctx: &rquickjs::Ctx<'js>, zval: &Zval) -> rquickjs::Result<Va
         Ok(Value::new_bool(ctx.clone(), b))
     } else if zval.is_long() {
         let n = zval.long().unwrap_or(0);
-        Ok(Value::new_int(ctx.clone(), n as i32))
+        // Numbers outside the int32 range are doubles in JavaScript
+        match i32::try_from(n) {
+            Ok(n) => Ok(Value::new_int(ctx.clone(), n)),
+            Err(_) => Ok(Value::new_float(ctx.clone(), n as f64)),
+        }
     } else if zval.is_double() {
         let n = zval.double().unwrap_or(0.0);
         Ok(Value::new_float(ctx.clone(), n))
@@ -24,52 +197,230 @@ fn zval_to_js<'js>(ctx: &rquickjs::Ctx<'js>, zval: &Zval) -> rquickjs::Result<Va
     } else if zval.is_array() {
         let arr = zval.array().unwrap();
 
-        // Check if it's a sequential array (list) or associative array (object)
-        let mut is_sequential = true;
-        let mut expected_index = 0i64;
-
-        for (key, _) in arr.iter() {
-            match key {
-                ArrayKey::Long(idx) if idx == expected_index => {
-                    expected_index += 1;
-                }
-                _ => {
-                    is_sequential = false;
-                    break;
-                }
-            }
+        // Arrays only become circular through references, which share the hashtable
+        let entered = path
+            .enter(arr as *const ZendHashTable as usize)
+            .map_err(|e| rquickjs::Error::new_into_js_message("array", "value", e))?;
+        if !entered {
+            return rquickjs::String::from_str(ctx.clone(), CIRCULAR_MARKER).map(|s| s.into());
         }
 
-        if is_sequential {
-            // Convert to JavaScript Array
-            let js_arr = Array::new(ctx.clone())?;
-            for (i, (_, val)) in arr.iter().enumerate() {
-                let js_val = zval_to_js(ctx, val)?;
-                js_arr.set(i, js_val)?;
-            }
-            Ok(js_arr.into())
-        } else {
-            // Convert to JavaScript Object
-            let obj = Object::new(ctx.clone())?;
-            for (key, val) in arr.iter() {
-                let key_str = match key {
-                    ArrayKey::Long(idx) => idx.to_string(),
-                    ArrayKey::String(s) => s.to_string(),
-                    ArrayKey::Str(s) => s.to_string(),
-                };
-                let js_val = zval_to_js(ctx, val)?;
-                obj.set(&key_str, js_val)?;
-            }
-            Ok(obj.into())
-        }
+        let value = hashtable_to_js(ctx, arr, path)?;
+        path.leave();
+        Ok(value)
+    } else if zval.is_object() {
+        object_to_js(ctx, zval, path)
     } else {
         // Default to null for unsupported types
         Ok(Value::new_null(ctx.clone()))
     }
 }
 
+/// Converts a PHP array to a JS Array when its keys are exactly `0..n`, otherwise to an object
+///
+/// Object keys follow JavaScript's property order: integer keys first in ascending
+/// order, then string keys in insertion order.
+fn hashtable_to_js<'js>(
+    ctx: &rquickjs::Ctx<'js>,
+    arr: &ZendHashTable,
+    path: &mut ConversionPath<usize>,
+) -> rquickjs::Result<Value<'js>> {
+    let is_list = arr
+        .iter()
+        .enumerate()
+        .all(|(i, (key, _))| matches!(key, ArrayKey::Long(idx) if idx == i as i64));
+
+    if is_list && !(arr.is_empty() && path.options.empty_array_as_object) {
+        let js_arr = Array::new(ctx.clone())?;
+        for (i, (_, val)) in arr.iter().enumerate() {
+            let js_val = convert_zval(ctx, val, path)?;
+            js_arr.set(i, js_val)?;
+        }
+        return Ok(js_arr.into());
+    }
+
+    let obj = Object::new(ctx.clone())?;
+    for (key, val) in arr.iter() {
+        let key_str = match key {
+            ArrayKey::Long(idx) => idx.to_string(),
+            ArrayKey::String(s) => s.to_string(),
+            ArrayKey::Str(s) => s.to_string(),
+        };
+        let js_val = convert_zval(ctx, val, path)?;
+        obj.set(&key_str, js_val)?;
+    }
+    Ok(obj.into())
+}
+
+/// Converts supported PHP objects: QuickBinary to `Uint8Array`, objects of a PHP class
+/// registered with a QuickClass to its instances, DateTimeInterface to `Date` and
+/// `stdClass` to a plain object
+fn object_to_js<'js>(
+    ctx: &rquickjs::Ctx<'js>,
+    zval: &Zval,
+    path: &mut ConversionPath<usize>,
+) -> rquickjs::Result<Value<'js>> {
+    if let Some(obj) = zval.object().filter(|obj| obj.get_class_name().is_ok_and(|name| name == "stdClass")) {
+        let entered = path
+            .enter(obj as *const _ as usize)
+            .map_err(|e| rquickjs::Error::new_into_js_message("stdClass", "object", e))?;
+        if !entered {
+            return rquickjs::String::from_str(ctx.clone(), CIRCULAR_MARKER).map(|s| s.into());
+        }
+
+        let properties = obj
+            .get_properties()
+            .map_err(|e| rquickjs::Error::new_into_js_message("stdClass", "object", format!("{:?}", e)))?;
+        // An object without properties stays an object instead of becoming `[]`
+        let value = if properties.is_empty() {
+            Object::new(ctx.clone())?.into()
+        } else {
+            hashtable_to_js(ctx, properties, path)?
+        };
+
+        path.leave();
+        return Ok(value);
+    }
+
+    if let Some(binary) = <&QuickBinary>::from_zval(zval) {
+        return TypedArray::<u8>::new_copy(ctx.clone(), &binary.data).map(|array| array.into_value());
+    }
+
+    if let Some(instance) = wrap_instance(ctx, zval)? {
+        return Ok(instance);
+    }
+
+    let is_date = match (zval.object(), ClassEntry::try_find("DateTimeInterface")) {
+        (Some(obj), Some(ce)) => obj.instance_of(ce),
+        _ => false,
+    };
+    if is_date {
+        let ms = date_time_to_ms(zval).map_err(|e| Exception::throw_message(ctx, &e))?;
+        let date: Constructor = ctx.globals().get("Date")?;
+        return date.construct((ms,));
+    }
+
+    Ok(Value::new_null(ctx.clone()))
+}
+
+/// Copies the parts of a PHP value that `zval_to_js` reads, for replaying it later
+///
+/// Arrays and `stdClass` objects are copied recursively and dates by their time, so
+/// changing the original in PHP does not change the copy. QuickBinary is immutable
+/// and other objects are kept as they are. Circular references become the marker
+/// or fail like in `zval_to_js`.
+fn detach_zval(zval: &Zval, path: &mut ConversionPath<usize>) -> Result<Zval, String> {
+    let zval = zval.reference().unwrap_or(zval);
+
+    if let Some(arr) = zval.array() {
+        if !path.enter(arr as *const ZendHashTable as usize)? {
+            return circular_marker_zval();
+        }
+
+        let mut copy = ZendHashTable::new();
+        for (key, value) in arr.iter() {
+            copy.insert(key, detach_zval(value, path)?)
+                .map_err(|e| format!("Failed to copy array: {:?}", e))?;
+        }
+        path.leave();
+
+        let mut zval = Zval::new();
+        zval.set_hashtable(copy);
+        return Ok(zval);
+    }
+
+    let Some(obj) = zval.object() else {
+        return Ok(zval.shallow_clone());
+    };
+
+    if obj.get_class_name().is_ok_and(|name| name == "stdClass") {
+        if !path.enter(obj as *const _ as usize)? {
+            return circular_marker_zval();
+        }
+
+        let properties = obj
+            .get_properties()
+            .map_err(|e| format!("Failed to read object: {:?}", e))?;
+        let mut copy = ZendObject::new_stdclass();
+        for (key, value) in properties.iter() {
+            copy.set_property(&key.to_string(), detach_zval(value, path)?)
+                .map_err(|e| format!("Failed to copy object: {:?}", e))?;
+        }
+        path.leave();
+
+        return copy.into_zval(false).map_err(|e| format!("Failed to copy object: {:?}", e));
+    }
+
+    if ClassEntry::try_find("DateTimeInterface").is_some_and(|ce| obj.instance_of(ce)) {
+        return ms_to_date_time(date_time_to_ms(zval)?);
+    }
+
+    Ok(zval.shallow_clone())
+}
+
+fn circular_marker_zval() -> Result<Zval, String> {
+    let mut zval = Zval::new();
+    zval.set_string(CIRCULAR_MARKER, false)
+        .map_err(|e| format!("Failed to set string: {:?}", e))?;
+    Ok(zval)
+}
+
+/// Milliseconds since the epoch of a DateTimeInterface
+///
+/// Seconds and milliseconds are read as separate integers: before 1970 the seconds
+/// are negative while the milliseconds still count forward, so `-2` and `500` are
+/// -1500 ms, not the -2500 ms a `U.v` decimal reads as.
+fn date_time_to_ms(zval: &Zval) -> Result<f64, String> {
+    let date_format = ZendCallable::try_from_name("date_format")
+        .map_err(|e| format!("Failed to get date_format: {:?}", e))?;
+    let format = "U v".to_string();
+    let args: Vec<&dyn IntoZvalDyn> = vec![zval, &format];
+    let result = date_format
+        .try_call(args)
+        .map_err(|e| format!("Failed to format date: {:?}", e))?;
+
+    let (seconds, millis) = result
+        .string()
+        .and_then(|s| {
+            let (seconds, millis) = s.split_once(' ')?;
+            Some((seconds.parse::<i64>().ok()?, millis.parse::<i64>().ok()?))
+        })
+        .ok_or_else(|| "Failed to read date timestamp".to_string())?;
+    Ok((seconds * 1000 + millis) as f64)
+}
+
+/// Creates a UTC `DateTimeImmutable` from milliseconds since the epoch, null for invalid dates
+fn ms_to_date_time(ms: f64) -> Result<Zval, String> {
+    if !ms.is_finite() {
+        let mut zval = Zval::new();
+        zval.set_null();
+        return Ok(zval);
+    }
+
+    let micros = (ms * 1000.0).round() as i64;
+    let time = format!("{}.{:06}", micros.div_euclid(1_000_000), micros.rem_euclid(1_000_000));
+    let format = "U.u".to_string();
+
+    let create = ZendCallable::try_from_name("DateTimeImmutable::createFromFormat")
+        .map_err(|e| format!("Failed to get DateTimeImmutable::createFromFormat: {:?}", e))?;
+    let args: Vec<&dyn IntoZvalDyn> = vec![&format, &time];
+    let date = create
+        .try_call(args)
+        .map_err(|e| format!("Failed to create DateTimeImmutable: {:?}", e))?;
+
+    if !date.is_object() {
+        return Err(format!("Failed to create DateTimeImmutable from '{}'", time));
+    }
+    Ok(date)
+}
+
 /// Converts a QuickJS Value to a PHP Zval
 fn js_to_zval(value: &Value<'_>) -> Result<Zval, String> {
+    let mut path = ConversionPath::new(ConversionOptions::of(value.ctx()));
+    convert_value(value, &mut path)
+}
+
+fn convert_value<'js>(value: &Value<'js>, path: &mut ConversionPath<Value<'js>>) -> Result<Zval, String> {
     let mut zval = Zval::new();
 
     if value.is_null() || value.is_undefined() {
@@ -89,155 +440,318 @@ fn js_to_zval(value: &Value<'_>) -> Result<Zval, String> {
             .map_err(|e| format!("String conversion error: {:?}", e))?;
         zval.set_string(&rust_str, false)
             .map_err(|e| format!("Failed to set string: {:?}", e))?;
-    } else if value.is_array() {
-        let arr = value.as_array().unwrap();
+    } else if value.is_function() {
+        // Functions are returned as a string representation
+        zval.set_string("[Function]", false)
+            .map_err(|e| format!("Failed to set string: {:?}", e))?;
+    } else if value.is_object() {
+        if !path.enter(value.clone())? {
+            zval.set_string(CIRCULAR_MARKER, false)
+                .map_err(|e| format!("Failed to set string: {:?}", e))?;
+            return Ok(zval);
+        }
+        zval = convert_object(value, path)?;
+        path.leave();
+    } else {
+        zval.set_null();
+    }
+
+    Ok(zval)
+}
+
+/// Converts arrays, built-in objects and plain objects
+fn convert_object<'js>(value: &Value<'js>, path: &mut ConversionPath<Value<'js>>) -> Result<Zval, String> {
+    let mut zval = Zval::new();
+
+    if let Some(arr) = value.as_array() {
         let mut php_arr = ext_php_rs::types::ZendHashTable::new();
 
+        // Holes of sparse arrays are skipped, the other elements keep their index
         for i in 0..arr.len() {
+            if !arr.as_object().contains_key(i as u32).unwrap_or(false) {
+                continue;
+            }
             if let Ok(item) = arr.get::<Value>(i) {
-                let item_zval = js_to_zval(&item)?;
+                let item_zval = convert_value(&item, path)?;
                 php_arr
-                    .push(item_zval)
-                    .map_err(|e| format!("Failed to push to array: {:?}", e))?;
+                    .insert_at_index(i as i64, item_zval)
+                    .map_err(|e| format!("Failed to insert to array: {:?}", e))?;
             }
         }
         zval.set_hashtable(php_arr);
-    } else if value.is_object() {
-        let obj = value.as_object().unwrap();
-        let mut php_arr = ext_php_rs::types::ZendHashTable::new();
+        return Ok(zval);
+    }
 
-        // Get all enumerable properties
-        for key in obj.keys::<String>() {
-            if let Ok(key) = key {
-                if let Ok(val) = obj.get::<_, Value>(&key) {
-                    let val_zval = js_to_zval(&val)?;
-                    php_arr
-                        .insert(key.as_str(), val_zval)
-                        .map_err(|e| format!("Failed to insert to array: {:?}", e))?;
-                }
-            }
+    let obj = value.as_object().unwrap();
+    if let Some(object) = backing_object(obj) {
+        return Ok(object);
+    }
+    if let Some(builtin) = builtin_to_zval(obj, path)? {
+        return Ok(builtin);
+    }
+
+    if path.options.objects_as_stdclass {
+        return object_to_stdclass(obj, path);
+    }
+
+    let mut php_arr = ext_php_rs::types::ZendHashTable::new();
+
+    // Get all enumerable properties
+    for key in obj.keys::<String>().flatten() {
+        if let Ok(val) = obj.get::<_, Value>(&key) {
+            let val_zval = convert_value(&val, path)?;
+            insert_property(&mut php_arr, &key, val_zval)?;
         }
-        zval.set_hashtable(php_arr);
-    } else if value.is_function() {
-        // Functions are returned as a string representation
-        zval.set_string("[Function]", false)
-            .map_err(|e| format!("Failed to set string: {:?}", e))?;
-    } else {
-        zval.set_null();
     }
+    zval.set_hashtable(php_arr);
 
     Ok(zval)
 }
 
-/// Clone a Zval for storage - returns the cloned data as simple types
-#[derive(Clone)]
-enum StoredValue {
-    Null,
-    Bool(bool),
-    Long(i64),
-    Double(f64),
-    String(String),
-    Array(Vec<(String, StoredValue)>),
-}
-
-impl StoredValue {
-    fn from_zval(val: &Zval) -> Self {
-        if val.is_null() {
-            StoredValue::Null
-        } else if val.is_bool() {
-            StoredValue::Bool(val.bool().unwrap_or(false))
-        } else if val.is_long() {
-            StoredValue::Long(val.long().unwrap_or(0))
-        } else if val.is_double() {
-            StoredValue::Double(val.double().unwrap_or(0.0))
-        } else if val.is_string() {
-            StoredValue::String(val.string().unwrap_or_default())
-        } else if val.is_array() {
-            if let Some(arr) = val.array() {
-                let mut items = Vec::new();
-                for (k, v) in arr.iter() {
-                    let key = match k {
-                        ArrayKey::Long(idx) => idx.to_string(),
-                        ArrayKey::String(s) => s.to_string(),
-                        ArrayKey::Str(s) => s.to_string(),
-                    };
-                    items.push((key, StoredValue::from_zval(v)));
-                }
-                StoredValue::Array(items)
-            } else {
-                StoredValue::Null
-            }
-        } else {
-            StoredValue::Null
+/// Converts the enumerable properties of a plain object to a `stdClass`
+///
+/// Unlike arrays this keeps `{}` an object and numeric keys like `"0"` strings, so
+/// the value converts back to the same JavaScript object.
+fn object_to_stdclass<'js>(obj: &Object<'js>, path: &mut ConversionPath<Value<'js>>) -> Result<Zval, String> {
+    let mut php_obj = ZendObject::new_stdclass();
+
+    for key in obj.keys::<String>().flatten() {
+        if let Ok(val) = obj.get::<_, Value>(&key) {
+            let val_zval = convert_value(&val, path)?;
+            php_obj
+                .set_property(&key, val_zval)
+                .map_err(|e| format!("Failed to set property: {:?}", e))?;
         }
     }
 
-    fn to_js<'js>(&self, ctx: &rquickjs::Ctx<'js>) -> rquickjs::Result<Value<'js>> {
-        match self {
-            StoredValue::Null => Ok(Value::new_null(ctx.clone())),
-            StoredValue::Bool(b) => Ok(Value::new_bool(ctx.clone(), *b)),
-            StoredValue::Long(n) => Ok(Value::new_int(ctx.clone(), *n as i32)),
-            StoredValue::Double(n) => Ok(Value::new_float(ctx.clone(), *n)),
-            StoredValue::String(s) => {
-                rquickjs::String::from_str(ctx.clone(), s).map(|s| s.into())
-            }
-            StoredValue::Array(items) => {
-                // Check if it's sequential (all numeric keys starting from 0)
-                let is_sequential = items.iter().enumerate().all(|(i, (k, _))| {
-                    k.parse::<usize>().map(|n| n == i).unwrap_or(false)
-                });
-
-                if is_sequential && !items.is_empty() {
-                    let arr = Array::new(ctx.clone())?;
-                    for (i, (_, v)) in items.iter().enumerate() {
-                        arr.set(i, v.to_js(ctx)?)?;
-                    }
-                    Ok(arr.into())
-                } else {
-                    let obj = Object::new(ctx.clone())?;
-                    for (k, v) in items {
-                        obj.set(k.as_str(), v.to_js(ctx)?)?;
-                    }
-                    Ok(obj.into())
-                }
-            }
+    php_obj.into_zval(false).map_err(|e| format!("Failed to create object: {:?}", e))
+}
+
+/// Converts built-in objects to their PHP counterparts, `None` for other objects
+///
+/// `Uint8Array` and `ArrayBuffer` become binary strings, other typed arrays lists,
+/// `Date` a `DateTimeImmutable`, `Map` an associative array, `Set` a list and
+/// `RegExp` its source like `/ab+c/i`.
+fn builtin_to_zval<'js>(
+    obj: &Object<'js>,
+    path: &mut ConversionPath<Value<'js>>,
+) -> Result<Option<Zval>, String> {
+    let raw = obj.as_raw();
+    let mut zval = Zval::new();
+
+    if let Some(array) = obj.as_typed_array::<u8>() {
+        zval.set_binary(array.as_bytes().unwrap_or(&[]).to_vec());
+    } else if let Some(buffer) = obj.as_array_buffer() {
+        zval.set_binary(buffer.as_bytes().unwrap_or(&[]).to_vec());
+    } else if unsafe { qjs::JS_GetTypedArrayType(raw) } >= 0 {
+        let length: u32 = obj
+            .get("length")
+            .map_err(|e| format!("Failed to get typed array length: {:?}", e))?;
+        let mut php_arr = ext_php_rs::types::ZendHashTable::new();
+        for i in 0..length {
+            let item: Value = obj
+                .get(i)
+                .map_err(|e| format!("Failed to get typed array item: {:?}", e))?;
+            php_arr
+                .push(convert_value(&item, path)?)
+                .map_err(|e| format!("Failed to push to array: {:?}", e))?;
+        }
+        zval.set_hashtable(php_arr);
+    } else if unsafe { qjs::JS_IsDate(raw) } {
+        let ms: f64 = call_builtin_method(obj, "getTime")?;
+        zval = ms_to_date_time(ms)?;
+    } else if unsafe { qjs::JS_IsRegExp(raw) } {
+        let source: Coerced<String> = call_builtin_method(obj, "toString")?;
+        zval.set_string(&source.0, false)
+            .map_err(|e| format!("Failed to set string: {:?}", e))?;
+    } else if unsafe { qjs::JS_IsMap(raw) } {
+        let mut php_arr = ext_php_rs::types::ZendHashTable::new();
+        for entry in iterate(obj, "entries")? {
+            let entry = entry.as_array().ok_or_else(|| "Invalid Map entry".to_string())?;
+            let key: Value = entry.get(0).map_err(|e| format!("Failed to get Map key: {:?}", e))?;
+            let value: Value = entry.get(1).map_err(|e| format!("Failed to get Map value: {:?}", e))?;
+            let value = convert_value(&value, path)?;
+
+            let key: Coerced<String> = key.get().map_err(|e| format!("Invalid Map key: {:?}", e))?;
+            insert_property(&mut php_arr, &key.0, value)?;
         }
+        zval.set_hashtable(php_arr);
+    } else if is_set(obj) {
+        let mut php_arr = ext_php_rs::types::ZendHashTable::new();
+        for item in iterate(obj, "values")? {
+            php_arr
+                .push(convert_value(&item, path)?)
+                .map_err(|e| format!("Failed to push to array: {:?}", e))?;
+        }
+        zval.set_hashtable(php_arr);
+    } else {
+        return Ok(None);
+    }
+
+    Ok(Some(zval))
+}
+
+/// Insert a JS property into a PHP array, integer-like keys such as `"42"` become integer keys
+///
+/// PHP treats `$array["42"]` as `$array[42]`, a string key `"42"` could not be read back.
+fn insert_property(arr: &mut ZendHashTable, key: &str, value: Zval) -> Result<(), String> {
+    let inserted = match integer_key(key) {
+        Some(index) => arr.insert_at_index(index, value),
+        None => arr.insert(key, value),
+    };
+    inserted.map_err(|e| format!("Failed to insert to array: {:?}", e))
+}
+
+/// The integer PHP uses for a string array key: decimal without leading zeros or `+`
+fn integer_key(key: &str) -> Option<i64> {
+    let digits = key.strip_prefix('-').unwrap_or(key);
+    let canonical = match digits.as_bytes() {
+        [] => false,
+        [b'0'] => digits.len() == key.len(),
+        [first, rest @ ..] => first.is_ascii_digit() && *first != b'0' && rest.iter().all(u8::is_ascii_digit),
+    };
+    if canonical { key.parse().ok() } else { None }
+}
+
+/// Call a method without arguments on a built-in object
+fn call_builtin_method<'js, R: rquickjs::FromJs<'js>>(obj: &Object<'js>, method: &str) -> Result<R, String> {
+    let func: JsFunction = obj
+        .get(method)
+        .map_err(|e| format!("Failed to get {}: {:?}", method, e))?;
+    func.call((rquickjs::function::This(obj.clone()),))
+        .map_err(|e| format!("Failed to call {}: {:?}", method, e))
+}
+
+/// Check for a `Set`, which QuickJS has no class check for
+fn is_set(obj: &Object<'_>) -> bool {
+    obj.ctx()
+        .globals()
+        .get::<_, Value>("Set")
+        .map(|set| set.is_function() && obj.is_instance_of(&set))
+        .unwrap_or(false)
+}
+
+/// Collect the values of the iterator returned by `obj[method]()`
+fn iterate<'js>(obj: &Object<'js>, method: &str) -> Result<Vec<Value<'js>>, String> {
+    let iterator: Object = call_builtin_method(obj, method)?;
+    let next: JsFunction = iterator
+        .get("next")
+        .map_err(|e| format!("Failed to get iterator: {:?}", e))?;
+
+    let mut values = Vec::new();
+    loop {
+        let result: Object = next
+            .call((rquickjs::function::This(iterator.clone()),))
+            .map_err(|e| format!("Failed to iterate: {:?}", e))?;
+        if result.get::<_, bool>("done").unwrap_or(true) {
+            return Ok(values);
+        }
+        values.push(result.get("value").map_err(|e| format!("Failed to iterate: {:?}", e))?);
     }
 }
 
 /// Represents a property in a QuickObject
-#[derive(Clone)]
+///
+/// Property values are kept as Zvals and converted by `zval_to_js` when the object
+/// is registered, exactly like values passed to `setGlobal()`.
 enum QuickObjectMember {
-    Property(StoredValue),
+    Property(Zval),
     Function(String),
     NestedObject(QuickObjectData),
+    LiveObject(Rc<RefCell<QuickObjectData>>),
+    Accessor(Zval, Option<Zval>),
+}
+
+impl Clone for QuickObjectMember {
+    fn clone(&self) -> Self {
+        match self {
+            QuickObjectMember::Property(value) => QuickObjectMember::Property(value.shallow_clone()),
+            QuickObjectMember::Function(func_name) => QuickObjectMember::Function(func_name.clone()),
+            QuickObjectMember::NestedObject(data) => QuickObjectMember::NestedObject(data.clone()),
+            QuickObjectMember::LiveObject(data) => QuickObjectMember::LiveObject(data.clone()),
+            QuickObjectMember::Accessor(get, set) => {
+                QuickObjectMember::Accessor(get.shallow_clone(), set.as_ref().map(Zval::shallow_clone))
+            }
+        }
+    }
+}
+
+/// Attributes of a QuickObject member, all enabled like for plain assignments
+///
+/// `writable` does not apply to getters, which are writable when they have a setter.
+#[derive(Clone, Copy)]
+struct PropertyAttributes {
+    writable: bool,
+    enumerable: bool,
+    configurable: bool,
+}
+
+impl PropertyAttributes {
+    /// Read the `writable`, `enumerable` and `configurable` entries
+    fn from_array(attributes: Option<&ZendHashTable>) -> Self {
+        PropertyAttributes {
+            writable: option_bool(attributes, "writable", true),
+            enumerable: option_bool(attributes, "enumerable", true),
+            configurable: option_bool(attributes, "configurable", true),
+        }
+    }
+
+    /// Define `name` on `obj` as a data property with these attributes
+    fn define<'js>(self, obj: &Object<'js>, name: &str, value: Value<'js>) -> rquickjs::Result<()> {
+        let mut property = Property::from(value);
+        if self.writable {
+            property = property.writable();
+        }
+        if self.enumerable {
+            property = property.enumerable();
+        }
+        if self.configurable {
+            property = property.configurable();
+        }
+        obj.prop(name, property)
+    }
+
+    /// Define `name` on `obj` as an accessor property, ignoring `writable`
+    fn define_accessor<'js, G, S, P>(self, obj: &Object<'js>, name: &str, accessor: Accessor<G, S>) -> rquickjs::Result<()>
+    where
+        Accessor<G, S>: AsProperty<'js, P>,
+    {
+        let mut accessor = accessor;
+        if self.enumerable {
+            accessor = accessor.enumerable();
+        }
+        if self.configurable {
+            accessor = accessor.configurable();
+        }
+        obj.prop(name, accessor)
+    }
 }
 
 /// Internal data structure for QuickObject
 #[derive(Clone, Default)]
 struct QuickObjectData {
-    members: Vec<(String, QuickObjectMember)>,
+    members: Vec<(String, QuickObjectMember, PropertyAttributes)>,
+    frozen: bool,
 }
 
 impl QuickObjectData {
     fn new() -> Self {
-        Self { members: Vec::new() }
+        Self::default()
     }
 
-    fn add_property(&mut self, name: String, value: StoredValue) {
+    fn add_member(&mut self, name: String, member: QuickObjectMember, attributes: PropertyAttributes) {
         // Remove existing member with same name if any
-        self.members.retain(|(n, _)| n != &name);
-        self.members.push((name, QuickObjectMember::Property(value)));
+        self.members.retain(|(n, _, _)| n != &name);
+        self.members.push((name, member, attributes));
     }
 
-    fn add_function(&mut self, name: String, php_func: String) {
-        self.members.retain(|(n, _)| n != &name);
-        self.members.push((name, QuickObjectMember::Function(php_func)));
-    }
-
-    fn add_object(&mut self, name: String, obj: QuickObjectData) {
-        self.members.retain(|(n, _)| n != &name);
-        self.members.push((name, QuickObjectMember::NestedObject(obj)));
+    /// Check whether `target` is linked as a live object somewhere below this object
+    fn links_live(&self, target: &Rc<RefCell<QuickObjectData>>) -> bool {
+        self.members.iter().any(|(_, member, _)| match member {
+            QuickObjectMember::NestedObject(data) => data.links_live(target),
+            QuickObjectMember::LiveObject(data) => Rc::ptr_eq(data, target) || data.borrow().links_live(target),
+            _ => false,
+        })
     }
 }
 
@@ -258,7 +772,7 @@ impl QuickObjectData {
 #[php_class]
 #[php(name = "Shopware\\PHPExtension\\QuickJS\\QuickObject")]
 pub struct QuickObject {
-    data: RefCell<QuickObjectData>,
+    data: Rc<RefCell<QuickObjectData>>,
 }
 
 #[php_impl]
@@ -266,31 +780,128 @@ impl QuickObject {
     /// Create a new QuickObject
     pub fn __construct() -> Self {
         QuickObject {
-            data: RefCell::new(QuickObjectData::new()),
+            data: Rc::new(RefCell::new(QuickObjectData::new())),
         }
     }
 
     /// Register a property with a value
-    pub fn register_property(&self, name: &str, value: &Zval) -> PhpResult<()> {
-        let stored = StoredValue::from_zval(value);
-        self.data.borrow_mut().add_property(name.to_string(), stored);
+    ///
+    /// The value is converted like `QuickJS::setGlobal()` does when the object is
+    /// registered, so dates, binary data and empty arrays behave the same.
+    ///
+    /// `$attributes` may disable `writable`, `enumerable` and `configurable`, e.g.
+    /// `['writable' => false]` for a value scripts cannot change.
+    #[php(optional = attributes)]
+    pub fn register_property(&self, name: &str, value: &Zval, attributes: Option<&ZendHashTable>) -> PhpResult<()> {
+        self.data.borrow_mut().add_member(
+            name.to_string(),
+            QuickObjectMember::Property(value.shallow_clone()),
+            PropertyAttributes::from_array(attributes),
+        );
         Ok(())
     }
 
+    /// Freeze the object when it is registered, like `Object.freeze()`
+    ///
+    /// Scripts can then neither add, change nor delete its members. A frozen object
+    /// registered as a global can also not be replaced. Nested objects are frozen
+    /// only when they are frozen themselves.
+    pub fn freeze(&self) {
+        self.data.borrow_mut().frozen = true;
+    }
+
     /// Register a PHP function as a method
-    pub fn register_function(&self, name: &str, php_function_name: &str) -> PhpResult<()> {
+    ///
+    /// Accepts the same `$attributes` as `registerProperty()`, e.g.
+    /// `['writable' => false, 'enumerable' => false]` for a method scripts can neither
+    /// replace nor see in `Object.keys()`.
+    #[php(optional = attributes)]
+    pub fn register_function(
+        &self,
+        name: &str,
+        php_function_name: &str,
+        attributes: Option<&ZendHashTable>,
+    ) -> PhpResult<()> {
         // Validate that the function exists
         ZendCallable::try_from_name(php_function_name)
             .map_err(|e| PhpException::default(format!("Invalid callable '{}': {:?}", php_function_name, e)))?;
-        
-        self.data.borrow_mut().add_function(name.to_string(), php_function_name.to_string());
+
+        self.data.borrow_mut().add_member(
+            name.to_string(),
+            QuickObjectMember::Function(php_function_name.to_string()),
+            PropertyAttributes::from_array(attributes),
+        );
+        Ok(())
+    }
+
+    /// Register a property whose value is read from and written to PHP on access
+    ///
+    /// `$get` is called without arguments on every read and `$set` with the new value
+    /// on every write. Without `$set` the property is read-only. `$attributes` may
+    /// disable `enumerable` and `configurable`; `writable` does not apply, a getter is
+    /// writable exactly when it has a setter.
+    ///
+    /// Example:
+    /// ```php
+    /// $shop->registerGetter('currency', fn () => $context->getCurrency()->getIsoCode(),
+    ///     fn (string $iso) => $context->switchCurrency($iso));
+    /// $shop->registerGetter('sessionId', fn () => $session->getId(), null, ['enumerable' => false]);
+    /// ```
+    #[php(optional = set)]
+    pub fn register_getter(
+        &self,
+        name: &str,
+        get: &Zval,
+        set: Option<&Zval>,
+        attributes: Option<&ZendHashTable>,
+    ) -> PhpResult<()> {
+        if !get.is_callable() {
+            return Err(PhpException::default(format!("Getter of '{}' must be callable", name)));
+        }
+        let set = set.filter(|set| !set.is_null());
+        if set.is_some_and(|set| !set.is_callable()) {
+            return Err(PhpException::default(format!("Setter of '{}' must be callable", name)));
+        }
+        if attributes.is_some_and(|attributes| attributes.get("writable").is_some()) {
+            return Err(PhpException::default(format!(
+                "Getter '{}' cannot have a writable attribute, pass a setter instead",
+                name
+            )));
+        }
+
+        self.data.borrow_mut().add_member(
+            name.to_string(),
+            QuickObjectMember::Accessor(get.shallow_clone(), set.map(Zval::shallow_clone)),
+            PropertyAttributes::from_array(attributes),
+        );
         Ok(())
     }
 
     /// Register a nested QuickObject
-    pub fn register_object(&self, name: &str, obj: &QuickObject) -> PhpResult<()> {
-        let nested_data = obj.data.borrow().clone();
-        self.data.borrow_mut().add_object(name.to_string(), nested_data);
+    ///
+    /// The nested object is copied unless `['live' => true]` is passed, see
+    /// `QuickJS::registerObject()`. Throws when the object would end up inside
+    /// itself through a live link, e.g. `$obj->registerObject('self', $obj, ['live' => true])`.
+    /// `$options` also accepts the attributes of `registerProperty()`, e.g.
+    /// `['writable' => false]` so scripts cannot replace the nested object.
+    #[php(optional = options)]
+    pub fn register_object(&self, name: &str, obj: &QuickObject, options: Option<&ZendHashTable>) -> PhpResult<()> {
+        let live = option_bool(options, "live", false);
+        if (live && Rc::ptr_eq(&obj.data, &self.data)) || obj.data.borrow().links_live(&self.data) {
+            return Err(PhpException::default(format!(
+                "Cannot register '{}', a live QuickObject cannot be nested inside itself",
+                name
+            )));
+        }
+
+        let member = if live {
+            QuickObjectMember::LiveObject(obj.data.clone())
+        } else {
+            QuickObjectMember::NestedObject(obj.data.borrow().clone())
+        };
+        self.data
+            .borrow_mut()
+            .add_member(name.to_string(), member, PropertyAttributes::from_array(options));
         Ok(())
     }
 }
@@ -300,61 +911,483 @@ impl QuickObject {
     fn get_data(&self) -> QuickObjectData {
         self.data.borrow().clone()
     }
+
+    /// Get the shared data for live bindings
+    fn get_live_data(&self) -> Rc<RefCell<QuickObjectData>> {
+        self.data.clone()
+    }
+}
+
+/// QuickBinary - Marks a string as binary data
+///
+/// PHP strings are passed to JavaScript as strings. Wrap raw bytes in a QuickBinary
+/// to pass them as a `Uint8Array` instead; `Uint8Array` and `ArrayBuffer` values
+/// come back to PHP as binary strings.
+///
+/// Example:
+/// ```php
+/// $js->setGlobal('image', new QuickBinary(file_get_contents('logo.png')));
+/// $js->eval('image.length');
+/// ```
+#[php_class]
+#[php(name = "Shopware\\PHPExtension\\QuickJS\\QuickBinary")]
+pub struct QuickBinary {
+    data: Vec<u8>,
+}
+
+#[php_impl]
+impl QuickBinary {
+    /// Wrap a binary string
+    pub fn __construct(data: &Zval) -> PhpResult<Self> {
+        let data = data
+            .zend_str()
+            .map(|s| s.as_bytes().to_vec())
+            .ok_or_else(|| PhpException::default("Binary data must be a string".to_string()))?;
+
+        Ok(QuickBinary { data })
+    }
+
+    /// Get the wrapped bytes as a binary string
+    pub fn get_data(&self) -> Zval {
+        let mut zval = Zval::new();
+        zval.set_binary(self.data.clone());
+        zval
+    }
+
+    /// Number of bytes
+    pub fn length(&self) -> i64 {
+        self.data.len() as i64
+    }
 }
 
 /// Helper function to build a JS object from QuickObjectData
 fn build_js_object<'js>(ctx: &rquickjs::Ctx<'js>, data: &QuickObjectData) -> rquickjs::Result<Object<'js>> {
     let obj = Object::new(ctx.clone())?;
 
-    for (name, member) in &data.members {
-        match member {
-            QuickObjectMember::Property(value) => {
-                let js_val = value.to_js(ctx)?;
-                obj.set(name.as_str(), js_val)?;
-            }
+    for (name, member, attributes) in &data.members {
+        let value = match member {
+            QuickObjectMember::Property(value) => zval_to_js(ctx, value)?,
             QuickObjectMember::Function(func_name) => {
                 let callback = PhpFunctionCallback {
                     func_name: func_name.clone(),
                 };
-                let func = rquickjs::Function::new(ctx.clone(), callback)?;
-                obj.set(name.as_str(), func)?;
+                rquickjs::Function::new(ctx.clone(), callback)?.into_value()
             }
-            QuickObjectMember::NestedObject(nested_data) => {
-                let nested_obj = build_js_object(ctx, nested_data)?;
-                obj.set(name.as_str(), nested_obj)?;
+            QuickObjectMember::NestedObject(nested_data) => build_js_object(ctx, nested_data)?.into_value(),
+            QuickObjectMember::LiveObject(nested_data) => live_object(ctx, nested_data)?,
+            QuickObjectMember::Accessor(get, set) => {
+                let get = PhpCallableCallback {
+                    callable: get.shallow_clone(),
+                };
+                match set {
+                    Some(set) => {
+                        let set = PhpCallableCallback {
+                            callable: set.shallow_clone(),
+                        };
+                        attributes.define_accessor(&obj, name, Accessor::new(get, set))?;
+                    }
+                    None => attributes.define_accessor(&obj, name, Accessor::new_get(get))?,
+                }
+                continue;
             }
-        }
+        };
+        attributes.define(&obj, name, value)?;
+    }
+
+    if data.frozen {
+        let object: Object = ctx.globals().get("Object")?;
+        let freeze: JsFunction = object.get("freeze")?;
+        freeze.call::<_, Value>((obj.clone(),))?;
     }
 
     Ok(obj)
 }
 
-/// QuickJS JavaScript engine class for PHP
-/// 
-/// This class provides a simple interface to execute JavaScript code from PHP.
-/// It combines runtime and context management into a single, easy-to-use class.
-#[php_class]
-#[php(name = "Shopware\\PHPExtension\\QuickJS\\QuickJS")]
-pub struct QuickJS {
-    runtime: Arc<Runtime>,
-    context: RefCell<Context>,
+/// Names accepted by the `intrinsics` constructor option
+const INTRINSICS: &[&str] = &[
+    "date",
+    "eval",
+    "regexp",
+    "json",
+    "proxy",
+    "map_set",
+    "typed_arrays",
+    "promise",
+    "bigint",
+    "performance",
+    "weakref",
+];
+
+/// Parse the `intrinsics` constructor option, `None` means all intrinsics
+fn intrinsics_from_options(options: Option<&ZendHashTable>) -> PhpResult<Option<Vec<String>>> {
+    let list = match options.and_then(|opts| opts.get("intrinsics")) {
+        Some(list) => list,
+        None => return Ok(None),
+    };
+    let list = list
+        .array()
+        .ok_or_else(|| PhpException::default("Option 'intrinsics' must be an array".to_string()))?;
+
+    let mut intrinsics = Vec::new();
+    for (_, name) in list.iter() {
+        let name = name.string().unwrap_or_default().to_lowercase();
+        if !INTRINSICS.contains(&name.as_str()) {
+            return Err(PhpException::default(format!(
+                "Unknown intrinsic '{}', expected one of: {}",
+                name,
+                INTRINSICS.join(", ")
+            )));
+        }
+        intrinsics.push(name);
+    }
+
+    Ok(Some(intrinsics))
 }
 
-#[php_impl]
+/// The full `JSMemoryUsage` of a runtime as an associative array
+fn memory_stats(runtime: &Runtime) -> Zval {
+    let usage = runtime.memory_usage();
+    let fields = [
+        ("malloc_size", usage.malloc_size),
+        ("malloc_limit", usage.malloc_limit),
+        ("memory_used_size", usage.memory_used_size),
+        ("malloc_count", usage.malloc_count),
+        ("memory_used_count", usage.memory_used_count),
+        ("atom_count", usage.atom_count),
+        ("atom_size", usage.atom_size),
+        ("str_count", usage.str_count),
+        ("str_size", usage.str_size),
+        ("obj_count", usage.obj_count),
+        ("obj_size", usage.obj_size),
+        ("prop_count", usage.prop_count),
+        ("prop_size", usage.prop_size),
+        ("shape_count", usage.shape_count),
+        ("shape_size", usage.shape_size),
+        ("js_func_count", usage.js_func_count),
+        ("js_func_size", usage.js_func_size),
+        ("js_func_code_size", usage.js_func_code_size),
+        ("js_func_pc2line_count", usage.js_func_pc2line_count),
+        ("js_func_pc2line_size", usage.js_func_pc2line_size),
+        ("c_func_count", usage.c_func_count),
+        ("array_count", usage.array_count),
+        ("fast_array_count", usage.fast_array_count),
+        ("fast_array_elements", usage.fast_array_elements),
+        ("binary_object_count", usage.binary_object_count),
+        ("binary_object_size", usage.binary_object_size),
+    ];
+
+    let mut arr = ZendHashTable::new();
+    for (key, value) in fields {
+        let _ = arr.insert(key, value);
+    }
+
+    let mut zval = Zval::new();
+    zval.set_hashtable(arr);
+    zval
+}
+
+/// Create a context with the given intrinsics, or all of them when `None`
+fn create_context(runtime: &Runtime, intrinsics: Option<&[String]>) -> PhpResult<Context> {
+    let intrinsics = match intrinsics {
+        Some(intrinsics) => intrinsics,
+        None => {
+            return Context::full(runtime)
+                .map_err(|e| PhpException::default(format!("Failed to create context: {:?}", e)));
+        }
+    };
+
+    let context = Context::base(runtime)
+        .map_err(|e| PhpException::default(format!("Failed to create context: {:?}", e)))?;
+    let enabled = |name: &str| intrinsics.iter().any(|i| i == name);
+    let raw = context.as_raw();
+
+    // Evaluating scripts from PHP goes through the eval intrinsic, so it is always added;
+    // without the "eval" intrinsic only the JS entry points to it are removed below.
+    unsafe {
+        intrinsic::Eval::add_intrinsic(raw);
+        if enabled("date") {
+            intrinsic::Date::add_intrinsic(raw);
+        }
+        if enabled("regexp") {
+            intrinsic::RegExpCompiler::add_intrinsic(raw);
+            intrinsic::RegExp::add_intrinsic(raw);
+        }
+        if enabled("json") {
+            intrinsic::Json::add_intrinsic(raw);
+        }
+        if enabled("proxy") {
+            intrinsic::Proxy::add_intrinsic(raw);
+        }
+        if enabled("map_set") {
+            intrinsic::MapSet::add_intrinsic(raw);
+        }
+        if enabled("typed_arrays") {
+            intrinsic::TypedArrays::add_intrinsic(raw);
+        }
+        if enabled("promise") {
+            intrinsic::Promise::add_intrinsic(raw);
+        }
+        if enabled("bigint") {
+            intrinsic::BigInt::add_intrinsic(raw);
+        }
+        if enabled("performance") {
+            intrinsic::Performance::add_intrinsic(raw);
+        }
+        if enabled("weakref") {
+            intrinsic::WeakRef::add_intrinsic(raw);
+        }
+    }
+
+    if !enabled("eval") {
+        context.with(|ctx| {
+            ctx.eval::<(), _>(DISABLE_EVAL_SCRIPT)
+                .map_err(|e| PhpException::default(format!("Failed to disable eval: {:?}", e)))
+        })?;
+    }
+
+    Ok(context)
+}
+
+/// Removes `eval` and replaces every function constructor, which compile code from strings
+const DISABLE_EVAL_SCRIPT: &str = r#"
+(() => {
+    const blocked = function () {
+        throw new EvalError('Code generation from strings is disabled');
+    };
+    const samples = [
+        () => function () {},
+        () => function* () {},
+        () => async function () {},
+        () => async function* () {},
+    ];
+    for (const sample of samples) {
+        try {
+            Object.defineProperty(Object.getPrototypeOf(sample()), 'constructor', { value: blocked });
+        } catch (e) {}
+    }
+    delete globalThis.eval;
+    delete globalThis.Function;
+})();
+"#;
+
+/// QuickJS JavaScript engine class for PHP
+/// 
+/// This class provides a simple interface to execute JavaScript code from PHP.
+/// It combines runtime and context management into a single, easy-to-use class.
+#[php_class]
+#[php(name = "Shopware\\PHPExtension\\QuickJS\\QuickJS")]
+pub struct QuickJS {
+    runtime: Arc<Runtime>,
+    context: RefCell<Context>,
+    intrinsics: Option<Vec<String>>,
+    /// Steps that changed the context, `None` unless snapshots are enabled
+    journal: RefCell<Option<Vec<SetupStep>>>,
+    /// Whether `snapshot()` was called, nothing is recorded afterwards
+    snapshot: Cell<bool>,
+    /// Nesting depth of calls using the context, only the outermost call is recorded
+    depth: Cell<usize>,
+    console: Rc<Console>,
+    /// Replaced together with the context by `restore()`
+    timers: RefCell<Rc<Timers>>,
+    /// Whether the timer functions are installed
+    timers_enabled: bool,
+}
+
+#[php_impl]
 impl QuickJS {
     /// Create a new QuickJS instance
-    pub fn __construct() -> PhpResult<Self> {
+    ///
+    /// Supported options:
+    /// - `intrinsics` (array, default all): built-in objects available to scripts, any of
+    ///   `date`, `eval`, `regexp`, `json`, `proxy`, `map_set`, `typed_arrays`, `promise`,
+    ///   `bigint`, `performance`, `weakref`. Without `eval`, scripts cannot use `eval()`
+    ///   or the `Function` constructor; PHP can still evaluate code.
+    /// - `snapshots` (bool, default false): record evaluated code, globals and
+    ///   registrations until `snapshot()`, so `restore()` can return to that state
+    /// - `console` (callable|LoggerInterface): receives `console.*` output as
+    ///   `(string $level, string $message)` with PSR-3 level names, e.g. `warning` for
+    ///   `console.warn()`. Without it, output is discarded.
+    /// - `console_capture` (bool, default false): also collect output for `getConsoleOutput()`
+    /// - `timers` (bool, default false): define `setTimeout`, `clearTimeout`, `setInterval`,
+    ///   `clearInterval` and `queueMicrotask`, run by `runEventLoop()`
+    /// - `max_depth` (int, default 512): deepest nesting of arrays and objects converted
+    ///   between PHP and JavaScript, deeper values throw
+    /// - `circular` (string, default `throw`): `throw` on circular references or `marker`
+    ///   to convert them to the string `[Circular]`
+    /// - `empty_array` (string, default `array`): pass empty PHP arrays to JavaScript as
+    ///   `[]` (`array`) or `{}` (`object`)
+    /// - `objects` (string, default `array`): return plain JavaScript objects to PHP as
+    ///   associative arrays (`array`) or as `stdClass` (`stdclass`), which converts back
+    ///   to the same object, also for `{}` and keys like `"0"`
+    ///
+    /// Example:
+    /// ```php
+    /// $js = new QuickJS(['intrinsics' => ['json', 'date']]);
+    /// $js = new QuickJS(['console' => $logger, 'console_capture' => true]);
+    /// ```
+    #[php(optional = options)]
+    pub fn __construct(options: Option<&ZendHashTable>) -> PhpResult<Self> {
+        let intrinsics = intrinsics_from_options(options)?;
+        let snapshots = option_bool(options, "snapshots", false);
+        let timers_enabled = option_bool(options, "timers", false);
+        let conversion = ConversionOptions::from_options(options)?;
+
         let runtime = Arc::new(Runtime::new()
             .map_err(|e| PhpException::default(format!("Failed to create runtime: {:?}", e)))?);
-        let context = Context::full(&runtime)
-            .map_err(|e| PhpException::default(format!("Failed to create context: {:?}", e)))?;
+        let context = create_context(&runtime, intrinsics.as_deref())?;
+        conversion.install(&context)?;
+        let console = Console::from_options(options)?;
+        console.install(&context)?;
+        let timers = Timers::new();
+        if timers_enabled {
+            timers.install(&context)?;
+        }
 
         Ok(QuickJS {
             runtime,
             context: RefCell::new(context),
+            intrinsics,
+            journal: RefCell::new(snapshots.then(Vec::new)),
+            snapshot: Cell::new(false),
+            depth: Cell::new(0),
+            console,
+            timers: RefCell::new(timers),
+            timers_enabled,
+        })
+    }
+
+    /// Mark the current state as the one `restore()` returns to
+    ///
+    /// Requires the `snapshots` option and can only be called once, typically after
+    /// loading libraries and registering functions, before evaluating per-request
+    /// scripts. Recording stops here, later changes are never replayed.
+    ///
+    /// Before the snapshot, `call()`, `apply()`, `callMethod()` and `runEventLoop()`
+    /// throw because replaying them would run PHP callbacks and timers again. Setup
+    /// code is evaluated with `eval()`: it is compiled once and its bytecode runs again
+    /// on each restore, so PHP functions it calls are called again. Values passed to
+    /// `setGlobal()` and registered objects are copied when recorded, changing them in
+    /// PHP afterwards does not affect restored contexts.
+    ///
+    /// Example:
+    /// ```php
+    /// $js = new QuickJS(['snapshots' => true]);
+    /// $js->evalFile('/app/storefront-lib.js');
+    /// $js->registerFunction('translate', 'translate');
+    /// $js->snapshot();
+    ///
+    /// foreach ($requests as $request) {
+    ///     $js->eval($request->script);
+    ///     $js->restore();
+    /// }
+    /// ```
+    pub fn snapshot(&self) -> PhpResult<()> {
+        if self.journal.borrow().is_none() {
+            return Err(snapshots_disabled());
+        }
+        if self.depth.get() > 0 {
+            return Err(PhpException::default("Cannot take a snapshot while JavaScript is running".to_string()));
+        }
+        if self.snapshot.get() {
+            return Err(PhpException::default("A snapshot has already been taken".to_string()));
+        }
+
+        self.snapshot.set(true);
+        Ok(())
+    }
+
+    /// Rebuild the context in the state of the last `snapshot()`
+    ///
+    /// A fresh context is created and everything done before the snapshot is
+    /// replayed, so no globals or modified built-ins leak from later scripts.
+    /// Library code runs again on each restore, its console output is discarded.
+    pub fn restore(&self) -> PhpResult<()> {
+        if self.depth.get() > 0 {
+            return Err(PhpException::default("Cannot restore while JavaScript is running".to_string()));
+        }
+
+        if self.journal.borrow().is_none() {
+            return Err(snapshots_disabled());
+        }
+        if !self.snapshot.get() {
+            return Err(PhpException::default("No snapshot has been taken".to_string()));
+        }
+
+        let context = create_context(&self.runtime, self.intrinsics.as_deref())?;
+        self.console.install(&context)?;
+        // The replay schedules timers of its own, pending ones stay with the old context
+        let timers = Timers::new();
+        if self.timers_enabled {
+            timers.install(&context)?;
+        }
+
+        let steps = self.journal.borrow_mut().take().unwrap_or_default();
+
+        self.depth.set(1);
+        self.console.set_muted(true);
+        let replayed = steps.iter().try_for_each(|step| step.apply(&context));
+        self.console.set_muted(false);
+        self.depth.set(0);
+
+        // Keep the journal even when replaying fails, the snapshot stays usable
+        *self.journal.borrow_mut() = Some(steps);
+        if let Err(e) = replayed {
+            // The old context keeps running with its timers, drop the ones of the new one
+            timers.clear();
+            return Err(e);
+        }
+
+        self.timers.replace(timers).clear();
+        *self.context.borrow_mut() = context;
+        self.runtime.run_gc();
+        Ok(())
+    }
+
+    /// Run queued microtasks, promise jobs and timers until idle or `$maxMs` passed
+    ///
+    /// Time is virtual: instead of sleeping, the clock jumps to the next due timer,
+    /// so `setTimeout(fn, 5000)` fires immediately and in a deterministic order.
+    /// `$maxMs` limits how far the clock advances, pass it when intervals are active.
+    /// Returns whether timers are still pending. Throws after 100000 callbacks in one
+    /// call, e.g. for an interval that is never cleared, instead of hanging, and
+    /// before `snapshot()` when snapshots are enabled.
+    ///
+    /// Example:
+    /// ```php
+    /// $js = new QuickJS(['timers' => true]);
+    /// $js->eval('setTimeout(() => { done = true }, 100); var done = false');
+    /// $js->runEventLoop(50);  // true, the timer is still pending
+    /// $js->runEventLoop();    // false, done is now true
+    /// ```
+    #[php(optional = max_ms)]
+    pub fn run_event_loop(&self, max_ms: Option<i64>) -> PhpResult<bool> {
+        self.refuse_while_recording("runEventLoop")?;
+        self.with_context(|context| self.timers.borrow().run(&self.runtime, context, max_ms))
+    }
+
+    /// Get the `console.*` output collected since the last `clearConsoleOutput()`
+    ///
+    /// Requires the `console_capture` option. Each line is prefixed with its level,
+    /// e.g. `[warning] Deprecated option`.
+    pub fn get_console_output(&self) -> PhpResult<String> {
+        self.console.output().ok_or_else(|| {
+            PhpException::default("Console capture is disabled, create QuickJS with ['console_capture' => true]".to_string())
         })
     }
 
+    /// Discard the collected console output
+    pub fn clear_console_output(&self) {
+        self.console.clear();
+    }
+
+    /// Freeze the global object so scripts can no longer add, replace or delete globals
+    ///
+    /// Register all functions and objects before freezing, afterwards `setGlobal` and
+    /// the register methods fail for new names.
+    pub fn freeze_globals(&self) -> PhpResult<()> {
+        self.journaled(|_| Ok(SetupStep::FreezeGlobals), context_freeze_globals)
+    }
+
     /// Set memory limit in bytes
     pub fn set_memory_limit(&self, limit: i64) -> PhpResult<()> {
         self.runtime.set_memory_limit(limit as usize);
@@ -373,176 +1406,191 @@ impl QuickJS {
         Ok(())
     }
 
+    /// Set the number of allocated bytes that triggers the next garbage collection
+    pub fn set_gc_threshold(&self, threshold: i64) -> PhpResult<()> {
+        self.runtime.set_gc_threshold(threshold as usize);
+        Ok(())
+    }
+
     /// Get memory usage in bytes
     pub fn memory_usage(&self) -> i64 {
         let usage = self.runtime.memory_usage();
-        usage.memory_used_size as i64
+        usage.memory_used_size
+    }
+
+    /// Get detailed memory statistics of the runtime
+    ///
+    /// Returns counts and sizes in bytes of the allocations, atoms, strings, objects,
+    /// properties, shapes, functions, arrays and binary objects, with the keys of
+    /// QuickJS' `JSMemoryUsage` like `malloc_count`, `obj_count` or `str_size`.
+    ///
+    /// Example:
+    /// ```php
+    /// $stats = $js->getMemoryStats();
+    /// echo $stats['memory_used_size'] . ' bytes in ' . $stats['obj_count'] . ' objects';
+    /// ```
+    pub fn get_memory_stats(&self) -> Zval {
+        memory_stats(&self.runtime)
     }
 
     /// Evaluate JavaScript code and return the result
     pub fn eval(&self, code: &str) -> PhpResult<Zval> {
-        let ctx = self.context.borrow();
-
-        ctx.with(|ctx| {
-            let result: Result<Value, _> = ctx.eval(code);
-
-            match result {
-                Ok(value) => js_to_zval(&value).map_err(|e| PhpException::default(e)),
-                Err(e) => Err(PhpException::default(format!("JavaScript error: {:?}", e))),
-            }
-        })
+        self.journaled(
+            |context| context_compile(context, code).map(|bytecode| SetupStep::Eval(bytecode, false)),
+            |context| context_eval(context, code),
+        )
     }
 
     /// Evaluate JavaScript code from a file
     pub fn eval_file(&self, filename: &str) -> PhpResult<Zval> {
-        let code = std::fs::read_to_string(filename)
-            .map_err(|e| PhpException::default(format!("Failed to read file: {:?}", e)))?;
-
-        let ctx = self.context.borrow();
-
-        ctx.with(|ctx| {
-            let result: Result<Value, _> = ctx.eval(code);
-
-            match result {
-                Ok(value) => js_to_zval(&value).map_err(|e| PhpException::default(e)),
-                Err(e) => Err(PhpException::default(format!("JavaScript error: {:?}", e))),
-            }
-        })
+        let code = read_script(filename)?;
+        self.eval(&code)
     }
 
     /// Set a global variable in the JavaScript context
     pub fn set_global(&self, name: &str, value: &Zval) -> PhpResult<()> {
-        let ctx = self.context.borrow();
-
-        ctx.with(|ctx| {
-            let js_value = zval_to_js(&ctx, value)
-                .map_err(|e| PhpException::default(format!("Failed to convert value: {:?}", e)))?;
-
-            let globals = ctx.globals();
-            globals
-                .set(name, js_value)
-                .map_err(|e| PhpException::default(format!("Failed to set global: {:?}", e)))?;
-
-            Ok(())
-        })
+        self.journaled(
+            |context| Ok(SetupStep::SetGlobal(name.to_string(), context_detach(context, value)?)),
+            |context| context_set_global(context, name, value),
+        )
     }
 
     /// Get a global variable from the JavaScript context
     pub fn get_global(&self, name: &str) -> PhpResult<Zval> {
-        let ctx = self.context.borrow();
-
-        ctx.with(|ctx| {
-            let globals = ctx.globals();
-            let value: Value = globals
-                .get(name)
-                .map_err(|e| PhpException::default(format!("Failed to get global: {:?}", e)))?;
-
-            js_to_zval(&value).map_err(|e| PhpException::default(e))
-        })
+        self.with_context(|context| context_get_global(context, name))
     }
 
-    /// Call a JavaScript function by name with arguments
-    pub fn call(&self, function_name: &str, args: &ext_php_rs::types::ZendHashTable) -> PhpResult<Zval> {
-        let ctx = self.context.borrow();
+    /// Set a global variable from a JSON string, parsed natively by QuickJS
+    ///
+    /// Much faster than `setGlobal()` for large data that is already JSON encoded.
+    ///
+    /// Example:
+    /// ```php
+    /// $js->setGlobalJson('products', json_encode($listing, JSON_THROW_ON_ERROR));
+    /// ```
+    pub fn set_global_json(&self, name: &str, json: &str) -> PhpResult<()> {
+        self.journaled(
+            |_| Ok(SetupStep::SetGlobalJson(name.to_string(), json.to_string())),
+            |context| context_set_global_json(context, name, json),
+        )
+    }
 
-        ctx.with(|ctx| {
-            let globals = ctx.globals();
-            let func: JsFunction = globals
-                .get(function_name)
-                .map_err(|e| PhpException::default(format!("Function not found: {:?}", e)))?;
+    /// Evaluate JavaScript code and return the result as a JSON string
+    ///
+    /// The result is stringified by QuickJS, `undefined` and functions become `null`.
+    ///
+    /// Example:
+    /// ```php
+    /// $result = json_decode($js->evalJson('render(products)'), true);
+    /// ```
+    pub fn eval_json(&self, code: &str) -> PhpResult<String> {
+        self.journaled(
+            |context| context_compile(context, code).map(|bytecode| SetupStep::Eval(bytecode, false)),
+            |context| context_eval_json(context, code),
+        )
+    }
 
-            // Convert PHP arguments to JS values
-            let mut js_args: Vec<Value> = Vec::new();
-            for (_, arg) in args.iter() {
-                let js_val = zval_to_js(&ctx, arg)
-                    .map_err(|e| PhpException::default(format!("Failed to convert argument: {:?}", e)))?;
-                js_args.push(js_val);
-            }
+    /// Call a JavaScript function by name or dotted path
+    ///
+    /// Each PHP argument becomes one JavaScript argument, so an associative array
+    /// arrives as a single options object. Functions reached through a dotted path
+    /// get their parent object as `this`. With snapshots enabled, calls are only
+    /// allowed after `snapshot()`.
+    ///
+    /// Example:
+    /// ```php
+    /// $js->call('greet', 'World');
+    /// $js->call('MyLib.utils.format', 1234.5, ['currency' => 'EUR']);
+    /// ```
+    pub fn call(&self, function_name: &str, args: &[&Zval]) -> PhpResult<Zval> {
+        self.refuse_while_recording("call")?;
+        self.with_context(|context| context_call(context, function_name, args, None))
+    }
 
-            // Call the function using Rest wrapper for variable args
-            let result: Value = func
-                .call((rquickjs::function::Rest(js_args),))
-                .map_err(|e| PhpException::default(format!("Function call failed: {:?}", e)))?;
+    /// Call a JavaScript function with a list of arguments and an optional `this`
+    ///
+    /// Like `Function.prototype.apply()`: `$args` must be a list, `$thisPath` is the
+    /// dotted path of the object used as `this`.
+    ///
+    /// Example:
+    /// ```php
+    /// $js->apply('Formatter.prototype.format', [1234.5], 'currencyFormatter');
+    /// ```
+    #[php(optional = this_path)]
+    pub fn apply(
+        &self,
+        function_name: &str,
+        args: &ext_php_rs::types::ZendHashTable,
+        this_path: Option<String>,
+    ) -> PhpResult<Zval> {
+        self.refuse_while_recording("apply")?;
+        let args = list_args(args)?;
+        self.with_context(|context| context_call(context, function_name, &args, this_path.as_deref()))
+    }
 
-            js_to_zval(&result).map_err(|e| PhpException::default(e))
-        })
+    /// Call a method of the object at a global dotted path with the object as `this`
+    ///
+    /// Example:
+    /// ```php
+    /// $js->eval('var cart = new Cart()');
+    /// $js->callMethod('cart', 'addItem', ['SW-1000', 2]);
+    /// ```
+    pub fn call_method(
+        &self,
+        object_path: &str,
+        method: &str,
+        args: &ext_php_rs::types::ZendHashTable,
+    ) -> PhpResult<Zval> {
+        self.refuse_while_recording("callMethod")?;
+        let args = list_args(args)?;
+        self.with_context(|context| context_call_method(context, object_path, method, &args))
     }
 
     /// Check if a global variable exists
     pub fn has_global(&self, name: &str) -> PhpResult<bool> {
-        let ctx = self.context.borrow();
-
-        ctx.with(|ctx| {
-            let globals = ctx.globals();
-            Ok(globals.contains_key(name).unwrap_or(false))
-        })
+        self.with_context(|context| context_has_global(context, name))
     }
 
-    /// Get the type of a global variable as a string
+    /// Get the type of a global variable or a dotted path as a string
+    ///
+    /// Like JavaScript's `typeof`, except that arrays report `array`, `null` reports
+    /// `null` and promises report `promise`.
+    ///
+    /// Example:
+    /// ```php
+    /// $js->typeofGlobal('MyLib.utils.format'); // "function"
+    /// $js->typeofGlobal('pending');            // "promise"
+    /// ```
     pub fn typeof_global(&self, name: &str) -> PhpResult<String> {
-        let ctx = self.context.borrow();
-
-        ctx.with(|ctx| {
-            let globals = ctx.globals();
-            let value: Value = globals
-                .get(name)
-                .map_err(|e| PhpException::default(format!("Failed to get global: {:?}", e)))?;
-
-            let type_str = if value.is_undefined() {
-                "undefined"
-            } else if value.is_null() {
-                "null"
-            } else if value.is_bool() {
-                "boolean"
-            } else if value.is_int() || value.is_float() {
-                "number"
-            } else if value.is_string() {
-                "string"
-            } else if value.is_array() {
-                "array"
-            } else if value.is_function() {
-                "function"
-            } else if value.is_object() {
-                "object"
-            } else {
-                "unknown"
-            };
+        self.with_context(|context| context_typeof(context, name))
+    }
 
-            Ok(type_str.to_string())
-        })
+    /// List the names of the enumerable global variables
+    ///
+    /// Built-ins and top-level `let`, `const` and `class` declarations are not listed.
+    pub fn list_globals(&self) -> PhpResult<Vec<String>> {
+        self.with_context(context_list_globals)
+    }
+
+    /// Describe the value of a global variable or a dotted path
+    ///
+    /// Example:
+    /// ```php
+    /// $js->describe('MyLib.utils.format');
+    /// // ['type' => 'function', 'keys' => ['length', 'name', 'prototype'],
+    /// //  'prototypes' => ['Function', 'Object'], 'name' => 'format', 'length' => 2]
+    /// ```
+    pub fn describe(&self, path: &str) -> PhpResult<Zval> {
+        self.with_context(|context| context_describe(context, path))
     }
 
     /// Register a PHP function (by name) as a JavaScript function
     /// Pass the function name as a string, e.g., "strtoupper", "array_sum"
     pub fn register_function(&self, js_name: &str, php_function_name: &str) -> PhpResult<()> {
-        // Validate that the function exists
-        ZendCallable::try_from_name(php_function_name)
-            .map_err(|e| PhpException::default(format!("Invalid callable '{}': {:?}", php_function_name, e)))?;
-
-        let php_func_name = php_function_name.to_string();
-        let js_name_owned = js_name.to_string();
-        let ctx = self.context.borrow();
-
-        ctx.with(|ctx| {
-            // Create a closure that captures the PHP function name
-            let callback = PhpFunctionCallback {
-                func_name: php_func_name.clone(),
-            };
-
-            // Create a Rust function that will be exposed to JS
-            let func = rquickjs::Function::new(ctx.clone(), callback)
-                .map_err(|e| PhpException::default(format!("Failed to create JS function: {:?}", e)))?;
-
-            // Register the function as a global
-            let globals = ctx.globals();
-            globals
-                .set(js_name_owned.as_str(), func)
-                .map_err(|e| {
-                    PhpException::default(format!("Failed to set global function: {:?}", e))
-                })?;
-
-            Ok(())
-        })
+        self.journaled(
+            |_| Ok(SetupStep::RegisterFunction(js_name.to_string(), php_function_name.to_string())),
+            |context| context_register_function(context, js_name, php_function_name),
+        )
     }
 
     /// Register a global JavaScript object using a QuickObject
@@ -554,91 +1602,841 @@ impl QuickJS {
     /// $obj->registerFunction('upper', 'strtoupper');
     /// $js->registerObject('MyApp', $obj);
     /// ```
-    #[php(name = "registerObject")]
-    pub fn register_object_from_quick_object(&self, js_name: &str, obj: &QuickObject) -> PhpResult<()> {
-        let js_name_owned = js_name.to_string();
+    ///
+    /// The object is copied on registration. With `['live' => true]` the JavaScript
+    /// object stays linked to the QuickObject instead: members added or changed in PHP
+    /// later are visible to scripts, and scripts assigning primitive values to
+    /// properties update the QuickObject. Assigning objects or functions, or replacing
+    /// functions and nested objects, throws a `TypeError`. Live objects require the
+    /// `proxy` intrinsic and read the QuickObject on every access, so functions and
+    /// nested objects are rebuilt and getters run each time a script reads or
+    /// enumerates them.
+    ///
+    /// ```php
+    /// $js->registerObject('Shop', $shop, ['live' => true]);
+    /// $shop->registerProperty('currency', 'USD'); // Shop.currency is now 'USD'
+    /// ```
+    #[php(name = "registerObject", optional = options)]
+    pub fn register_object_from_quick_object(
+        &self,
+        js_name: &str,
+        obj: &QuickObject,
+        options: Option<&ZendHashTable>,
+    ) -> PhpResult<()> {
+        if option_bool(options, "live", false) {
+            let data = obj.get_live_data();
+            return self.journaled(
+                |_| Ok(SetupStep::RegisterLiveObject(js_name.to_string(), data.clone())),
+                |context| context_register_live_object(context, js_name, &data),
+            );
+        }
+
         let data = obj.get_data();
-        let ctx = self.context.borrow();
-
-        ctx.with(|ctx| {
-            let js_obj = build_js_object(&ctx, &data)
-                .map_err(|e| PhpException::default(format!("Failed to build object: {:?}", e)))?;
-            
-            let globals = ctx.globals();
-            globals
-                .set(js_name_owned.as_str(), js_obj)
-                .map_err(|e| PhpException::default(format!("Failed to set global object: {:?}", e)))?;
-            
-            Ok(())
-        })
+        self.journaled(
+            |context| Ok(SetupStep::RegisterObject(js_name.to_string(), context_detach_object(context, &data)?)),
+            |context| context_register_object(context, js_name, &data),
+        )
+    }
+
+    /// Register a JavaScript class built with QuickClass
+    ///
+    /// Example:
+    /// ```php
+    /// $js->registerClass(new QuickClass('Money', fn (int $amount) => new Money($amount)));
+    /// $js->eval('new Money(1999) instanceof Money'); // true
+    /// ```
+    pub fn register_class(&self, class: &QuickClass) -> PhpResult<()> {
+        let data = class.get_data();
+        self.journaled(
+            |_| Ok(SetupStep::RegisterClass(data.clone())),
+            |context| context_register_class(context, &data),
+        )
     }
 
     /// Create or get a global object and add a method to it
     /// This allows building objects incrementally
     pub fn add_object_method(&self, object_name: &str, method_name: &str, php_function_name: &str) -> PhpResult<()> {
-        // Validate that the function exists
-        ZendCallable::try_from_name(php_function_name)
-            .map_err(|e| PhpException::default(format!("Invalid callable '{}': {:?}", php_function_name, e)))?;
+        self.journaled(
+            |_| Ok(SetupStep::AddObjectMethod(
+                object_name.to_string(),
+                method_name.to_string(),
+                php_function_name.to_string(),
+            )),
+            |context| context_add_object_method(context, object_name, method_name, php_function_name),
+        )
+    }
 
-        let php_func_name = php_function_name.to_string();
-        let ctx = self.context.borrow();
+    /// Add a property to an existing or new global object
+    pub fn add_object_property(&self, object_name: &str, property_name: &str, value: &Zval) -> PhpResult<()> {
+        self.journaled(
+            |context| Ok(SetupStep::AddObjectProperty(
+                object_name.to_string(),
+                property_name.to_string(),
+                context_detach(context, value)?,
+            )),
+            |context| context_add_object_property(context, object_name, property_name, value),
+        )
+    }
 
-        ctx.with(|ctx| {
-            let globals = ctx.globals();
+    /// Delete a global variable, function or object
+    ///
+    /// PHP callables only reachable through the global are released once QuickJS
+    /// frees the value, values in reference cycles on the next garbage collection.
+    /// Before `snapshot()` the recorded setup of a value created since the last
+    /// `eval()` is dropped as well, otherwise `restore()` keeps its callables.
+    /// Globals declared with `var` or `function` in scripts cannot be deleted and
+    /// throw, like the globals of a context with frozen globals.
+    ///
+    /// Example:
+    /// ```php
+    /// $js->setGlobal('request', $request);
+    /// $js->eval('handle(request)');
+    /// $js->deleteGlobal('request');
+    /// ```
+    pub fn delete_global(&self, name: &str) -> PhpResult<()> {
+        self.journaled(
+            |_| Ok(SetupStep::DeleteGlobal(name.to_string())),
+            |context| context_delete_global(context, name),
+        )
+    }
 
-            // Get or create the object
-            let obj: Object = if globals.contains_key(object_name).unwrap_or(false) {
-                globals.get(object_name)
-                    .map_err(|e| PhpException::default(format!("Failed to get object '{}': {:?}", object_name, e)))?
-            } else {
-                let new_obj = Object::new(ctx.clone())
-                    .map_err(|e| PhpException::default(format!("Failed to create object: {:?}", e)))?;
-                globals.set(object_name, new_obj.clone())
-                    .map_err(|e| PhpException::default(format!("Failed to set object: {:?}", e)))?;
-                new_obj
-            };
+    /// Remove a function registered with `registerFunction()`
+    ///
+    /// Throws if there is no global function with that name.
+    pub fn unregister_function(&self, js_name: &str) -> PhpResult<()> {
+        self.journaled(
+            |_| Ok(SetupStep::DeleteGlobal(js_name.to_string())),
+            |context| context_unregister_function(context, js_name),
+        )
+    }
 
-            // Create and add the method
-            let callback = PhpFunctionCallback {
-                func_name: php_func_name,
-            };
-            let func = rquickjs::Function::new(ctx.clone(), callback)
-                .map_err(|e| PhpException::default(format!("Failed to create method: {:?}", e)))?;
-            obj.set(method_name, func)
-                .map_err(|e| PhpException::default(format!("Failed to set method: {:?}", e)))?;
+    /// Remove a method or property from the object at a global dotted path
+    ///
+    /// Callables of members registered before `snapshot()` stay referenced until the
+    /// QuickJS object is destroyed, `restore()` registers them again.
+    ///
+    /// Example:
+    /// ```php
+    /// $js->addObjectMethod('Utils', 'upper', 'strtoupper');
+    /// $js->removeObjectMember('Utils', 'upper');
+    /// ```
+    pub fn remove_object_member(&self, object_path: &str, member: &str) -> PhpResult<()> {
+        self.journaled(
+            |_| Ok(SetupStep::RemoveObjectMember(object_path.to_string(), member.to_string())),
+            |context| context_remove_object_member(context, object_path, member),
+        )
+    }
+}
 
-            Ok(())
-        })
+impl QuickJS {
+    /// Run `f` with the context, raising the depth so `restore()` is refused meanwhile
+    ///
+    /// Every method that can run JavaScript goes through here, getters and proxy
+    /// traps may call back into PHP even when only reading a global.
+    fn with_context<R>(&self, f: impl FnOnce(&Context) -> PhpResult<R>) -> PhpResult<R> {
+        self.depth.set(self.depth.get() + 1);
+        let result = f(&self.context.borrow());
+        self.depth.set(self.depth.get() - 1);
+
+        result
     }
 
-    /// Add a property to an existing or new global object
-    pub fn add_object_property(&self, object_name: &str, property_name: &str, value: &Zval) -> PhpResult<()> {
-        let ctx = self.context.borrow();
+    /// Run `f` with the context and record the step built by `step` until `snapshot()`
+    ///
+    /// `step` runs first, so arguments are copied before scripts can change them.
+    /// Calls made from PHP callbacks while JavaScript is running are not recorded,
+    /// replaying the outer step repeats them.
+    fn journaled<R>(
+        &self,
+        step: impl FnOnce(&Context) -> PhpResult<SetupStep>,
+        f: impl FnOnce(&Context) -> PhpResult<R>,
+    ) -> PhpResult<R> {
+        if !self.recording() {
+            return self.with_context(f);
+        }
 
-        ctx.with(|ctx| {
-            let globals = ctx.globals();
+        let step = self.with_context(step)?;
+        let result = self.with_context(f);
 
-            // Get or create the object
-            let obj: Object = if globals.contains_key(object_name).unwrap_or(false) {
-                globals.get(object_name)
-                    .map_err(|e| PhpException::default(format!("Failed to get object '{}': {:?}", object_name, e)))?
-            } else {
-                let new_obj = Object::new(ctx.clone())
-                    .map_err(|e| PhpException::default(format!("Failed to create object: {:?}", e)))?;
-                globals.set(object_name, new_obj.clone())
-                    .map_err(|e| PhpException::default(format!("Failed to set object: {:?}", e)))?;
-                new_obj
-            };
+        if let Some(journal) = self.journal.borrow_mut().as_mut() {
+            match step {
+                // A script may have changed globals before it threw, so it is replayed as well
+                SetupStep::Eval(bytecode, _) => journal.push(SetupStep::Eval(bytecode, result.is_err())),
+                SetupStep::DeleteGlobal(name) if result.is_ok() => {
+                    compact_journal(journal, &name);
+                    journal.push(SetupStep::DeleteGlobal(name));
+                }
+                step if result.is_ok() => journal.push(step),
+                _ => {}
+            }
+        }
+
+        result
+    }
+
+    /// Whether changes are recorded: snapshots are enabled, none was taken yet and
+    /// no JavaScript is running
+    fn recording(&self) -> bool {
+        self.depth.get() == 0 && !self.snapshot.get() && self.journal.borrow().is_some()
+    }
+
+    /// Throw while recording for methods that run PHP callbacks and timers, which
+    /// `restore()` must not repeat
+    fn refuse_while_recording(&self, method: &str) -> PhpResult<()> {
+        if self.recording() {
+            return Err(PhpException::default(format!(
+                "{}() cannot be replayed by restore(), call snapshot() first or use eval() for setup code",
+                method
+            )));
+        }
+        Ok(())
+    }
+}
+
+impl Drop for QuickJS {
+    fn drop(&mut self) {
+        // Stored callbacks must be released while their context is alive
+        self.timers.borrow().clear();
+    }
+}
+
+/// A change made to a QuickJS context before `snapshot()`, replayed by `QuickJS::restore()`
+///
+/// Values are copied when recorded, see `detach_zval()`.
+enum SetupStep {
+    /// Compiled script and whether it threw when it was recorded
+    Eval(Vec<u8>, bool),
+    SetGlobal(String, Zval),
+    SetGlobalJson(String, String),
+    RegisterFunction(String, String),
+    RegisterObject(String, QuickObjectData),
+    RegisterLiveObject(String, Rc<RefCell<QuickObjectData>>),
+    RegisterClass(QuickClassData),
+    AddObjectMethod(String, String, String),
+    AddObjectProperty(String, String, Zval),
+    DeleteGlobal(String),
+    RemoveObjectMember(String, String),
+    FreezeGlobals,
+}
+
+/// Drop the steps since the last script that created or changed the deleted global `name`
+///
+/// Scripts before them may have kept a reference to the value, so changes to a value
+/// that existed when the last script ran are kept. Values created after it were only
+/// reachable through the global and are dropped with their PHP callables.
+fn compact_journal(journal: &mut Vec<SetupStep>, name: &str) {
+    let start = journal
+        .iter()
+        .rposition(|step| matches!(step, SetupStep::Eval(..)))
+        .map_or(0, |index| index + 1);
+
+    // Whether the global holds a value created after the last script
+    let mut created = false;
+    let steps = journal.split_off(start);
+    for step in steps {
+        match &step {
+            SetupStep::SetGlobal(global, _)
+            | SetupStep::SetGlobalJson(global, _)
+            | SetupStep::RegisterFunction(global, _)
+            | SetupStep::RegisterObject(global, _)
+            | SetupStep::RegisterLiveObject(global, _)
+            | SetupStep::DeleteGlobal(global)
+                if global == name =>
+            {
+                created = true;
+            }
+            SetupStep::AddObjectMethod(global, _, _) | SetupStep::AddObjectProperty(global, _, _)
+                if created && global == name => {}
+            SetupStep::RemoveObjectMember(path, _)
+                if created && (path == name || path.starts_with(&format!("{}.", name))) => {}
+            _ => journal.push(step),
+        }
+    }
+}
+
+impl SetupStep {
+    fn apply(&self, context: &Context) -> PhpResult<()> {
+        match self {
+            SetupStep::Eval(bytecode, failed) => match context_run_bytecode(context, bytecode) {
+                Err(_) if *failed => Ok(()),
+                result => result,
+            },
+            SetupStep::SetGlobal(name, value) => context_set_global(context, name, value),
+            SetupStep::SetGlobalJson(name, json) => context_set_global_json(context, name, json),
+            SetupStep::RegisterFunction(js_name, php_function_name) => {
+                context_register_function(context, js_name, php_function_name)
+            }
+            SetupStep::RegisterObject(js_name, data) => context_register_object(context, js_name, data),
+            SetupStep::RegisterLiveObject(js_name, data) => context_register_live_object(context, js_name, data),
+            SetupStep::RegisterClass(data) => context_register_class(context, data),
+            SetupStep::AddObjectMethod(object_name, method_name, php_function_name) => {
+                context_add_object_method(context, object_name, method_name, php_function_name)
+            }
+            SetupStep::AddObjectProperty(object_name, property_name, value) => {
+                context_add_object_property(context, object_name, property_name, value)
+            }
+            SetupStep::DeleteGlobal(name) => context_delete_global(context, name),
+            SetupStep::RemoveObjectMember(object_path, member) => {
+                context_remove_object_member(context, object_path, member)
+            }
+            SetupStep::FreezeGlobals => context_freeze_globals(context),
+        }
+    }
+}
+
+fn snapshots_disabled() -> PhpException {
+    PhpException::default("Snapshots are disabled, create QuickJS with ['snapshots' => true]".to_string())
+}
+
+/// Evaluate JavaScript code and return the result
+fn context_eval(context: &Context, code: &str) -> PhpResult<Zval> {
+    context.with(|ctx| {
+        let result: Result<Value, _> = ctx.eval(code);
+
+        match result {
+            Ok(value) => js_to_zval(&value).map_err(PhpException::default),
+            Err(e) => Err(PhpException::default(format!("JavaScript error: {:?}", e))),
+        }
+    })
+}
+
+/// Compile a script like `context_eval()` without running it, for `context_run_bytecode()`
+fn context_compile(context: &Context, code: &str) -> PhpResult<Vec<u8>> {
+    let source = std::ffi::CString::new(code)
+        .map_err(|_| PhpException::default("JavaScript code must not contain null bytes".to_string()))?;
+    let flags = qjs::JS_EVAL_TYPE_GLOBAL | qjs::JS_EVAL_FLAG_STRICT | qjs::JS_EVAL_FLAG_COMPILE_ONLY;
+
+    context.with(|ctx| {
+        let raw = ctx.as_raw().as_ptr();
+        let function = unsafe { qjs::JS_Eval(raw, source.as_ptr(), code.len() as _, c"eval_script".as_ptr(), flags as i32) };
+        if unsafe { qjs::JS_IsException(function) } {
+            return Err(caught_exception(&ctx));
+        }
+
+        let mut size = 0;
+        let buffer = unsafe { qjs::JS_WriteObject(raw, &mut size, function, qjs::JS_WRITE_OBJ_BYTECODE as i32) };
+        unsafe { qjs::JS_FreeValue(raw, function) };
+        if buffer.is_null() {
+            return Err(caught_exception(&ctx));
+        }
+
+        let bytecode = unsafe { std::slice::from_raw_parts(buffer, size as usize) }.to_vec();
+        unsafe { qjs::js_free(raw, buffer.cast()) };
+        Ok(bytecode)
+    })
+}
+
+/// Run a script compiled by `context_compile()`, ignoring its result
+fn context_run_bytecode(context: &Context, bytecode: &[u8]) -> PhpResult<()> {
+    context.with(|ctx| {
+        let raw = ctx.as_raw().as_ptr();
+        let function = unsafe {
+            qjs::JS_ReadObject(raw, bytecode.as_ptr(), bytecode.len() as _, qjs::JS_READ_OBJ_BYTECODE as i32)
+        };
+        // Evaluating takes ownership of the function
+        let result = if unsafe { qjs::JS_IsException(function) } {
+            function
+        } else {
+            unsafe { qjs::JS_EvalFunction(raw, function) }
+        };
+
+        if unsafe { qjs::JS_IsException(result) } {
+            return Err(caught_exception(&ctx));
+        }
+        unsafe { qjs::JS_FreeValue(raw, result) };
+        Ok(())
+    })
+}
+
+/// Take the pending JavaScript exception as a PHP exception
+fn caught_exception(ctx: &rquickjs::Ctx<'_>) -> PhpException {
+    let error = ctx.catch();
+    let message = error
+        .as_exception()
+        .and_then(Exception::message)
+        .unwrap_or_else(|| format!("{:?}", error));
+    PhpException::default(format!("JavaScript error: {}", message))
+}
+
+/// Copy a value for the journal with the conversion options of `context`
+fn context_detach(context: &Context, value: &Zval) -> PhpResult<Zval> {
+    context.with(|ctx| {
+        let mut path = ConversionPath::new(ConversionOptions::of(&ctx));
+        detach_zval(value, &mut path).map_err(|e| PhpException::default(format!("Failed to copy value: {}", e)))
+    })
+}
+
+/// Copy QuickObject data for the journal, see `context_detach()`
+fn context_detach_object(context: &Context, data: &QuickObjectData) -> PhpResult<QuickObjectData> {
+    let mut copy = data.clone();
+    for (_, member, _) in copy.members.iter_mut() {
+        match member {
+            QuickObjectMember::Property(value) => *value = context_detach(context, value)?,
+            QuickObjectMember::NestedObject(nested) => *nested = context_detach_object(context, nested)?,
+            _ => {}
+        }
+    }
+    Ok(copy)
+}
+
+/// Evaluate JavaScript code from a file
+fn context_eval_file(context: &Context, filename: &str) -> PhpResult<Zval> {
+    context_eval(context, &read_script(filename)?)
+}
+
+/// Read a script file for evaluation
+fn read_script(filename: &str) -> PhpResult<String> {
+    std::fs::read_to_string(filename)
+        .map_err(|e| PhpException::default(format!("Failed to read file: {:?}", e)))
+}
+
+/// Set a global variable in the JavaScript context
+fn context_set_global(context: &Context, name: &str, value: &Zval) -> PhpResult<()> {
+    context.with(|ctx| {
+        let js_value = zval_to_js(&ctx, value)
+            .map_err(|e| PhpException::default(format!("Failed to convert value: {:?}", e)))?;
+
+        let globals = ctx.globals();
+        globals
+            .set(name, js_value)
+            .map_err(|e| PhpException::default(format!("Failed to set global: {:?}", e)))?;
+
+        Ok(())
+    })
+}
+
+/// Set a global variable from a JSON string without converting through Zvals
+fn context_set_global_json(context: &Context, name: &str, json: &str) -> PhpResult<()> {
+    context.with(|ctx| {
+        let value = ctx
+            .json_parse(json)
+            .map_err(|e| PhpException::default(format!("Invalid JSON: {:?}", e)))?;
+
+        ctx.globals()
+            .set(name, value)
+            .map_err(|e| PhpException::default(format!("Failed to set global: {:?}", e)))?;
 
-            // Add the property
-            let js_val = zval_to_js(&ctx, value)
-                .map_err(|e| PhpException::default(format!("Failed to convert value: {:?}", e)))?;
-            obj.set(property_name, js_val)
-                .map_err(|e| PhpException::default(format!("Failed to set property: {:?}", e)))?;
+        Ok(())
+    })
+}
+
+/// Evaluate JavaScript code and stringify the result with `JSON.stringify`
+fn context_eval_json(context: &Context, code: &str) -> PhpResult<String> {
+    context.with(|ctx| {
+        let value: Value = ctx
+            .eval(code)
+            .map_err(|e| PhpException::default(format!("JavaScript error: {:?}", e)))?;
+
+        let json = ctx
+            .json_stringify(value)
+            .map_err(|e| PhpException::default(format!("Failed to encode result as JSON: {:?}", e)))?;
+
+        match json {
+            Some(json) => json
+                .to_string()
+                .map_err(|e| PhpException::default(format!("String conversion error: {:?}", e))),
+            None => Ok("null".to_string()),
+        }
+    })
+}
+
+/// Get a global variable from the JavaScript context
+fn context_get_global(context: &Context, name: &str) -> PhpResult<Zval> {
+    context.with(|ctx| {
+        let globals = ctx.globals();
+        let value: Value = globals
+            .get(name)
+            .map_err(|e| PhpException::default(format!("Failed to get global: {:?}", e)))?;
+
+        js_to_zval(&value).map_err(PhpException::default)
+    })
+}
+
+/// Call a JavaScript function by name or dotted path with arguments
+///
+/// A function reached through a dotted path is called with its parent object as
+/// `this`, like `MyLib.utils.format()` in JavaScript, unless `this_path` names
+/// another object.
+fn context_call(context: &Context, function_name: &str, args: &[&Zval], this_path: Option<&str>) -> PhpResult<Zval> {
+    context.with(|ctx| {
+        let (parent, value) = resolve_path(&ctx, function_name)?;
+        let func = value
+            .into_function()
+            .ok_or_else(|| PhpException::default(format!("Function not found: {}", function_name)))?;
+
+        let this = match this_path {
+            Some(path) => resolve_path(&ctx, path)?.1,
+            None => parent,
+        };
+
+        call_function(&ctx, func, this, args)
+    })
+}
+
+/// Call a method of the object at `object_path` with the object as `this`
+fn context_call_method(context: &Context, object_path: &str, method: &str, args: &[&Zval]) -> PhpResult<Zval> {
+    context.with(|ctx| {
+        let (_, object) = resolve_path(&ctx, object_path)?;
+        let func = object
+            .as_object()
+            .and_then(|obj| obj.get::<_, Value>(method).ok())
+            .and_then(|value| value.into_function())
+            .ok_or_else(|| PhpException::default(format!("Method not found: {}.{}", object_path, method)))?;
+
+        call_function(&ctx, func, object, args)
+    })
+}
 
-            Ok(())
+/// Positional arguments from a PHP list, associative arrays are rejected
+///
+/// Pass an associative array wrapped in a list to hand it over as an options object.
+fn list_args(args: &ext_php_rs::types::ZendHashTable) -> PhpResult<Vec<&Zval>> {
+    let mut list = Vec::new();
+    for (index, (key, arg)) in args.iter().enumerate() {
+        match key {
+            ArrayKey::Long(key) if key == index as i64 => list.push(arg),
+            _ => {
+                return Err(PhpException::default(
+                    "Arguments must be a list, wrap an associative array in a list to pass it as an object".to_string(),
+                ));
+            }
+        }
+    }
+
+    Ok(list)
+}
+
+/// Look up a dotted path like `MyLib.utils.format` starting at the global object
+///
+/// Returns the object holding the last segment together with its value.
+fn resolve_path<'js>(ctx: &rquickjs::Ctx<'js>, path: &str) -> PhpResult<(Value<'js>, Value<'js>)> {
+    let mut parent: Value = ctx.globals().into_value();
+    let mut current = parent.clone();
+
+    for (index, segment) in path.split('.').enumerate() {
+        if segment.is_empty() {
+            return Err(PhpException::default(format!("Invalid path '{}'", path)));
+        }
+
+        let object = current.as_object().ok_or_else(|| {
+            let walked: Vec<&str> = path.split('.').take(index).collect();
+            PhpException::default(format!("'{}' is not an object", walked.join(".")))
+        })?;
+        let value: Value = object
+            .get(segment)
+            .map_err(|e| PhpException::default(format!("Failed to get '{}': {:?}", path, e)))?;
+
+        parent = current;
+        current = value;
+    }
+
+    Ok((parent, current))
+}
+
+/// Convert the PHP arguments and call `func` with the given `this`
+fn call_function<'js>(
+    ctx: &rquickjs::Ctx<'js>,
+    func: JsFunction<'js>,
+    this: Value<'js>,
+    args: &[&Zval],
+) -> PhpResult<Zval> {
+    // Convert PHP arguments to JS values
+    let mut js_args: Vec<Value> = Vec::new();
+    for arg in args {
+        let js_val = zval_to_js(ctx, arg)
+            .map_err(|e| PhpException::default(format!("Failed to convert argument: {:?}", e)))?;
+        js_args.push(js_val);
+    }
+
+    // Call the function using Rest wrapper for variable args
+    let result: Value = func
+        .call((rquickjs::function::This(this), rquickjs::function::Rest(js_args)))
+        .map_err(|e| PhpException::default(format!("Function call failed: {:?}", e)))?;
+
+    js_to_zval(&result).map_err(PhpException::default)
+}
+
+/// Check if a global variable exists
+fn context_has_global(context: &Context, name: &str) -> PhpResult<bool> {
+    context.with(|ctx| {
+        let globals = ctx.globals();
+        Ok(globals.contains_key(name).unwrap_or(false))
+    })
+}
+
+/// Get the type of the value at a global dotted path as a string
+fn context_typeof(context: &Context, path: &str) -> PhpResult<String> {
+    context.with(|ctx| {
+        let (_, value) = resolve_path(&ctx, path)?;
+        Ok(type_name(&value).to_string())
+    })
+}
+
+/// The `typeof` of a value, with `array`, `null` and `promise` told apart from `object`
+fn type_name(value: &Value) -> &'static str {
+    match value.type_of() {
+        Type::Uninitialized | Type::Undefined => "undefined",
+        Type::Null => "null",
+        Type::Bool => "boolean",
+        Type::Int | Type::Float => "number",
+        Type::BigInt => "bigint",
+        Type::String => "string",
+        Type::Symbol => "symbol",
+        Type::Array => "array",
+        Type::Constructor | Type::Function => "function",
+        Type::Promise => "promise",
+        Type::Exception | Type::Object => "object",
+        Type::Module | Type::Unknown => "unknown",
+    }
+}
+
+/// Names of the enumerable own properties of the global object
+///
+/// Built-ins like `Object` or `JSON` are not enumerable and therefore not listed,
+/// neither are top-level `let`, `const` and `class` declarations, which do not
+/// become properties of the global object.
+fn context_list_globals(context: &Context) -> PhpResult<Vec<String>> {
+    context.with(|ctx| {
+        ctx.globals()
+            .keys::<String>()
+            .collect::<rquickjs::Result<Vec<String>>>()
+            .map_err(|e| PhpException::default(format!("Failed to list globals: {:?}", e)))
+    })
+}
+
+/// Describe the value at a global dotted path
+///
+/// Returns its `type`, all own string `keys` including non-enumerable ones and the
+/// constructor names of its `prototypes`. Functions also report their `name` and
+/// `length`.
+fn context_describe(context: &Context, path: &str) -> PhpResult<Zval> {
+    context.with(|ctx| {
+        let (_, value) = resolve_path(&ctx, path)?;
+        let describe_err = |e| PhpException::default(format!("Failed to describe '{}': {:?}", path, e));
+
+        let mut keys = Vec::new();
+        let mut prototypes = Vec::new();
+        if let Some(obj) = value.as_object() {
+            for key in obj.own_keys::<String>(Filter::new().string()) {
+                keys.push(key.map_err(describe_err)?);
+            }
+
+            let mut proto = obj.get_prototype();
+            while let Some(current) = proto {
+                prototypes.push(prototype_name(&current));
+                proto = current.get_prototype();
+            }
+        }
+
+        let mut arr = ZendHashTable::new();
+        let _ = arr.insert("type", type_name(&value));
+        let _ = arr.insert("keys", keys);
+        let _ = arr.insert("prototypes", prototypes);
+        if let Some(func) = value.as_function() {
+            let name: Coerced<String> = func.get("name").map_err(describe_err)?;
+            let length: i64 = func.get("length").map_err(describe_err)?;
+            let _ = arr.insert("name", name.0);
+            let _ = arr.insert("length", length);
+        }
+
+        let mut zval = Zval::new();
+        zval.set_hashtable(arr);
+        Ok(zval)
+    })
+}
+
+/// Name of the constructor owning a prototype, like `Array` for `Array.prototype`
+fn prototype_name(proto: &Object) -> String {
+    let constructor: Option<Object> = proto.get("constructor").ok();
+    constructor
+        .filter(|constructor| {
+            constructor
+                .get::<_, Value>("prototype")
+                .is_ok_and(|prototype| prototype == *proto.as_value())
         })
+        .and_then(|constructor| constructor.get::<_, String>("name").ok())
+        .filter(|name| !name.is_empty())
+        .unwrap_or_else(|| "(anonymous)".to_string())
+}
+
+/// Register a PHP function (by name) as a JavaScript function
+/// Pass the function name as a string, e.g., "strtoupper", "array_sum"
+fn context_register_function(context: &Context, js_name: &str, php_function_name: &str) -> PhpResult<()> {
+    // Validate that the function exists
+    ZendCallable::try_from_name(php_function_name)
+        .map_err(|e| PhpException::default(format!("Invalid callable '{}': {:?}", php_function_name, e)))?;
+
+    let php_func_name = php_function_name.to_string();
+    let js_name_owned = js_name.to_string();
+    context.with(|ctx| {
+        // Create a closure that captures the PHP function name
+        let callback = PhpFunctionCallback {
+            func_name: php_func_name.clone(),
+        };
+
+        // Create a Rust function that will be exposed to JS
+        let func = rquickjs::Function::new(ctx.clone(), callback)
+            .map_err(|e| PhpException::default(format!("Failed to create JS function: {:?}", e)))?;
+
+        // Register the function as a global
+        let globals = ctx.globals();
+        globals
+            .set(js_name_owned.as_str(), func)
+            .map_err(|e| {
+                PhpException::default(format!("Failed to set global function: {:?}", e))
+            })?;
+
+        Ok(())
+    })
+}
+
+/// Register a global JavaScript object using a QuickObject
+/// 
+/// Example:
+/// ```php
+/// $obj = new QuickObject();
+/// $obj->registerProperty('version', '1.0.0');
+/// $obj->registerFunction('upper', 'strtoupper');
+/// $js->registerObject('MyApp', $obj);
+/// ```
+fn context_register_object(context: &Context, js_name: &str, data: &QuickObjectData) -> PhpResult<()> {
+    context.with(|ctx| {
+        let js_obj = build_js_object(&ctx, data)
+            .map_err(|e| PhpException::default(format!("Failed to build object: {:?}", e)))?;
+
+        // A frozen object can not be swapped out through the global either
+        let globals = ctx.globals();
+        let defined = if data.frozen {
+            globals.prop(js_name, Property::from(js_obj).enumerable())
+        } else {
+            globals.set(js_name, js_obj)
+        };
+        defined.map_err(|e| PhpException::default(format!("Failed to set global object: {:?}", e)))?;
+
+        Ok(())
+    })
+}
+
+/// Create or get a global object and add a method to it
+/// This allows building objects incrementally
+fn context_add_object_method(context: &Context, object_name: &str, method_name: &str, php_function_name: &str) -> PhpResult<()> {
+    // Validate that the function exists
+    ZendCallable::try_from_name(php_function_name)
+        .map_err(|e| PhpException::default(format!("Invalid callable '{}': {:?}", php_function_name, e)))?;
+
+    let php_func_name = php_function_name.to_string();
+    context.with(|ctx| {
+        let globals = ctx.globals();
+
+        // Get or create the object
+        let obj: Object = if globals.contains_key(object_name).unwrap_or(false) {
+            globals.get(object_name)
+                .map_err(|e| PhpException::default(format!("Failed to get object '{}': {:?}", object_name, e)))?
+        } else {
+            let new_obj = Object::new(ctx.clone())
+                .map_err(|e| PhpException::default(format!("Failed to create object: {:?}", e)))?;
+            globals.set(object_name, new_obj.clone())
+                .map_err(|e| PhpException::default(format!("Failed to set object: {:?}", e)))?;
+            new_obj
+        };
+
+        // Create and add the method
+        let callback = PhpFunctionCallback {
+            func_name: php_func_name,
+        };
+        let func = rquickjs::Function::new(ctx.clone(), callback)
+            .map_err(|e| PhpException::default(format!("Failed to create method: {:?}", e)))?;
+        obj.set(method_name, func)
+            .map_err(|e| PhpException::default(format!("Failed to set method: {:?}", e)))?;
+
+        Ok(())
+    })
+}
+
+/// Add a property to an existing or new global object
+fn context_add_object_property(context: &Context, object_name: &str, property_name: &str, value: &Zval) -> PhpResult<()> {
+    context.with(|ctx| {
+        let globals = ctx.globals();
+
+        // Get or create the object
+        let obj: Object = if globals.contains_key(object_name).unwrap_or(false) {
+            globals.get(object_name)
+                .map_err(|e| PhpException::default(format!("Failed to get object '{}': {:?}", object_name, e)))?
+        } else {
+            let new_obj = Object::new(ctx.clone())
+                .map_err(|e| PhpException::default(format!("Failed to create object: {:?}", e)))?;
+            globals.set(object_name, new_obj.clone())
+                .map_err(|e| PhpException::default(format!("Failed to set object: {:?}", e)))?;
+            new_obj
+        };
+
+        // Add the property
+        let js_val = zval_to_js(&ctx, value)
+            .map_err(|e| PhpException::default(format!("Failed to convert value: {:?}", e)))?;
+        obj.set(property_name, js_val)
+            .map_err(|e| PhpException::default(format!("Failed to set property: {:?}", e)))?;
+
+        Ok(())
+    })
+}
+
+/// Delete a global variable, deleting a missing global does nothing
+fn context_delete_global(context: &Context, name: &str) -> PhpResult<()> {
+    context.with(|ctx| {
+        ctx.globals()
+            .remove(name)
+            .map_err(|e| PhpException::default(format!("Failed to delete global '{}': {:?}", name, e)))?;
+
+        Ok(())
+    })
+}
+
+/// Delete a global function registered with `registerFunction()`
+fn context_unregister_function(context: &Context, js_name: &str) -> PhpResult<()> {
+    let is_function = context.with(|ctx| {
+        ctx.globals()
+            .get::<_, Value>(js_name)
+            .is_ok_and(|value| value.is_function())
+    });
+    if !is_function {
+        return Err(PhpException::default(format!("Function not found: {}", js_name)));
     }
+
+    context_delete_global(context, js_name)
+}
+
+/// Remove a method or property from the object at a global dotted path
+fn context_remove_object_member(context: &Context, object_path: &str, member: &str) -> PhpResult<()> {
+    context.with(|ctx| {
+        let (_, object) = resolve_path(&ctx, object_path)?;
+        let obj = object
+            .as_object()
+            .ok_or_else(|| PhpException::default(format!("'{}' is not an object", object_path)))?;
+
+        obj.remove(member).map_err(|e| {
+            PhpException::default(format!("Failed to remove '{}.{}': {:?}", object_path, member, e))
+        })?;
+
+        Ok(())
+    })
+}
+
+/// Freeze the global object so scripts can no longer add, replace or delete globals
+fn context_freeze_globals(context: &Context) -> PhpResult<()> {
+    context.with(|ctx| {
+        let globals = ctx.globals();
+        let object: Object = globals
+            .get("Object")
+            .map_err(|e| PhpException::default(format!("Failed to get Object: {:?}", e)))?;
+        let freeze: JsFunction = object
+            .get("freeze")
+            .map_err(|e| PhpException::default(format!("Failed to get Object.freeze: {:?}", e)))?;
+        freeze
+            .call::<_, Value>((globals,))
+            .map_err(|e| PhpException::default(format!("Failed to freeze globals: {:?}", e)))?;
+
+        Ok(())
+    })
 }
 
 /// Struct to hold PHP function callback data
@@ -658,12 +2456,12 @@ impl<'js> rquickjs::function::IntoJsFunc<'js, (rquickjs::Ctx<'js>, rquickjs::fun
         // Get all arguments
         let mut php_args: Vec<Zval> = Vec::new();
         for i in 0..params.len() {
-            if let Some(arg) = params.arg(i) {
-                if let Ok(val) = arg.get::<Value>() {
-                    match js_to_zval(&val) {
-                        Ok(zval) => php_args.push(zval),
-                        Err(_) => return Err(rquickjs::Error::Unknown),
-                    }
+            if let Some(arg) = params.arg(i)
+                && let Ok(val) = arg.get::<Value>()
+            {
+                match js_to_zval(&val) {
+                    Ok(zval) => php_args.push(zval),
+                    Err(_) => return Err(rquickjs::Error::Unknown),
                 }
             }
         }
@@ -681,3 +2479,40 @@ impl<'js> rquickjs::function::IntoJsFunc<'js, (rquickjs::Ctx<'js>, rquickjs::fun
         zval_to_js(&ctx, &result).map_err(|_| rquickjs::Error::Unknown)
     }
 }
+
+/// A PHP callable (closure, `[$object, 'method']`, function name) called from JavaScript
+struct PhpCallableCallback {
+    callable: Zval,
+}
+
+impl Clone for PhpCallableCallback {
+    fn clone(&self) -> Self {
+        PhpCallableCallback {
+            callable: self.callable.shallow_clone(),
+        }
+    }
+}
+
+impl<'js> rquickjs::function::IntoJsFunc<'js, (rquickjs::Ctx<'js>, rquickjs::function::Rest<Value<'js>>)> for PhpCallableCallback {
+    fn param_requirements() -> rquickjs::function::ParamRequirement {
+        rquickjs::function::ParamRequirement::any()
+    }
+
+    fn call<'a>(&self, params: rquickjs::function::Params<'a, 'js>) -> rquickjs::Result<Value<'js>> {
+        let ctx = params.ctx().clone();
+
+        let mut php_args: Vec<Zval> = Vec::new();
+        for arg in (0..params.len()).filter_map(|i| params.arg(i)) {
+            php_args.push(js_to_zval(&arg).map_err(|e| Exception::throw_message(&ctx, &e))?);
+        }
+
+        let callable = ZendCallable::new(&self.callable)
+            .map_err(|e| Exception::throw_message(&ctx, &format!("Invalid callable: {:?}", e)))?;
+        let arg_refs: Vec<&dyn IntoZvalDyn> = php_args.iter().map(|z| z as &dyn IntoZvalDyn).collect();
+        let result = callable
+            .try_call(arg_refs)
+            .map_err(|e| Exception::throw_message(&ctx, &format!("PHP callable failed: {:?}", e)))?;
+
+        zval_to_js(&ctx, &result)
+    }
+}
@@ -0,0 +1,487 @@
+use ext_php_rs::convert::IntoZvalDyn;
+use ext_php_rs::prelude::*;
+use ext_php_rs::types::{ZendCallable, ZendHashTable, Zval};
+use ext_php_rs::zend::ClassEntry;
+use rquickjs::class::{JsClass, Readable, Trace, Tracer};
+use rquickjs::function::{Constructor, IntoJsFunc, ParamRequirement, Params};
+use rquickjs::object::{Accessor, Property};
+use rquickjs::{Array, Class, Context, Ctx, Exception, Function as JsFunction, JsLifetime, Object, Value};
+use std::cell::RefCell;
+
+use super::{js_to_zval, zval_to_js, PhpCallableCallback};
+
+/// QuickClass - A builder for JavaScript classes backed by PHP objects
+///
+/// The constructor callable receives the arguments of `new` and returns the PHP
+/// object behind the instance. Instance methods call the method of the same name on
+/// that object unless a callable is given, which then receives the object first.
+/// Properties read and write the public properties of the object. Instances passed
+/// back to PHP arrive as their PHP object.
+///
+/// With `$phpClass`, PHP objects of that class or a subclass become instances too when
+/// they are passed to JavaScript, e.g. returned from a method or set as a global.
+///
+/// Example usage:
+/// ```php
+/// $money = new QuickClass('Money', fn (int $amount, string $currency) => new Money($amount, $currency), Money::class);
+/// $money->registerMethod('format');
+/// $money->registerMethod('add', fn (Money $self, Money $other) => $self->add($other)->getAmount());
+/// $money->registerProperty('currency');
+/// $money->registerStaticMethod('currencies', fn () => ['EUR', 'USD']);
+///
+/// $js->registerClass($money);
+/// $js->eval('const price = new Money(1999, "EUR"); price instanceof Money && price.format()');
+///
+/// $js->setGlobal('total', new Money(4999, 'EUR'));
+/// $js->eval('total.format()');
+/// ```
+#[php_class]
+#[php(name = "Shopware\\PHPExtension\\QuickJS\\QuickClass")]
+pub struct QuickClass {
+    data: RefCell<QuickClassData>,
+}
+
+/// Definition of a QuickClass, converted to a JavaScript class on registration
+pub(super) struct QuickClassData {
+    name: String,
+    constructor: Zval,
+    php_class: Option<String>,
+    methods: Vec<(String, Option<Zval>)>,
+    static_methods: Vec<(String, Zval)>,
+    properties: Vec<String>,
+    static_properties: Vec<(String, Zval)>,
+}
+
+impl Clone for QuickClassData {
+    fn clone(&self) -> Self {
+        QuickClassData {
+            name: self.name.clone(),
+            constructor: self.constructor.shallow_clone(),
+            php_class: self.php_class.clone(),
+            methods: self
+                .methods
+                .iter()
+                .map(|(name, callable)| (name.clone(), callable.as_ref().map(Zval::shallow_clone)))
+                .collect(),
+            static_methods: self
+                .static_methods
+                .iter()
+                .map(|(name, callable)| (name.clone(), callable.shallow_clone()))
+                .collect(),
+            properties: self.properties.clone(),
+            static_properties: self
+                .static_properties
+                .iter()
+                .map(|(name, value)| (name.clone(), value.shallow_clone()))
+                .collect(),
+        }
+    }
+}
+
+#[php_impl]
+impl QuickClass {
+    /// Create a class named `$name` whose instances are created by `$constructor`
+    ///
+    /// Objects of `$phpClass` passed to JavaScript become instances of the class.
+    #[php(optional = php_class)]
+    pub fn __construct(name: &str, constructor: &Zval, php_class: Option<String>) -> PhpResult<Self> {
+        if name.is_empty() {
+            return Err(PhpException::default("Class name must not be empty".to_string()));
+        }
+        check_callable(constructor, "Constructor", name)?;
+
+        Ok(QuickClass {
+            data: RefCell::new(QuickClassData {
+                name: name.to_string(),
+                constructor: constructor.shallow_clone(),
+                php_class: php_class.filter(|class| !class.is_empty()),
+                methods: Vec::new(),
+                static_methods: Vec::new(),
+                properties: Vec::new(),
+                static_properties: Vec::new(),
+            }),
+        })
+    }
+
+    /// Register an instance method
+    ///
+    /// Without `$callable` the method of the same name of the PHP object is called,
+    /// otherwise `$callable` is called with the PHP object followed by the arguments.
+    #[php(optional = callable)]
+    pub fn register_method(&self, name: &str, callable: Option<&Zval>) -> PhpResult<()> {
+        let callable = callable.filter(|callable| !callable.is_null());
+        if let Some(callable) = callable {
+            check_callable(callable, "Method", name)?;
+        }
+
+        let mut data = self.data.borrow_mut();
+        data.methods.retain(|(n, _)| n != name);
+        data.methods.push((name.to_string(), callable.map(Zval::shallow_clone)));
+        Ok(())
+    }
+
+    /// Register a static method called with the arguments only
+    pub fn register_static_method(&self, name: &str, callable: &Zval) -> PhpResult<()> {
+        check_callable(callable, "Static method", name)?;
+
+        let mut data = self.data.borrow_mut();
+        data.static_methods.retain(|(n, _)| n != name);
+        data.static_methods.push((name.to_string(), callable.shallow_clone()));
+        Ok(())
+    }
+
+    /// Register an instance property backed by the public property of the PHP object
+    pub fn register_property(&self, name: &str) -> PhpResult<()> {
+        let mut data = self.data.borrow_mut();
+        if !data.properties.iter().any(|n| n == name) {
+            data.properties.push(name.to_string());
+        }
+        Ok(())
+    }
+
+    /// Register a static property with a value
+    pub fn register_static_property(&self, name: &str, value: &Zval) -> PhpResult<()> {
+        let mut data = self.data.borrow_mut();
+        data.static_properties.retain(|(n, _)| n != name);
+        data.static_properties.push((name.to_string(), value.shallow_clone()));
+        Ok(())
+    }
+}
+
+impl QuickClass {
+    /// Get the internal data for use by QuickJS (not exposed to PHP)
+    pub(super) fn get_data(&self) -> QuickClassData {
+        self.data.borrow().clone()
+    }
+}
+
+fn check_callable(callable: &Zval, kind: &str, name: &str) -> PhpResult<()> {
+    if callable.is_callable() {
+        Ok(())
+    } else {
+        Err(PhpException::default(format!("{} '{}' must be callable", kind, name)))
+    }
+}
+
+/// Define the class as a global in the JavaScript context
+pub(super) fn context_register_class(context: &Context, data: &QuickClassData) -> PhpResult<()> {
+    context.with(|ctx| {
+        let constructor = build_class(&ctx, data)
+            .map_err(|e| PhpException::default(format!("Failed to build class: {:?}", e)))?;
+        if let Some(php_class) = &data.php_class {
+            constructor
+                .get("prototype")
+                .and_then(|prototype| register_instance_class(&ctx, php_class, prototype))
+                .map_err(|e| PhpException::default(format!("Failed to register class: {:?}", e)))?;
+        }
+
+        ctx.globals()
+            .set(data.name.as_str(), constructor)
+            .map_err(|e| PhpException::default(format!("Failed to set global class: {:?}", e)))?;
+
+        Ok(())
+    })
+}
+
+/// Build the constructor function and prototype of a class
+fn build_class<'js>(ctx: &Ctx<'js>, data: &QuickClassData) -> rquickjs::Result<JsFunction<'js>> {
+    let constructor = JsFunction::new(
+        ctx.clone(),
+        ClassConstructor {
+            class: data.name.clone(),
+            constructor: data.constructor.shallow_clone(),
+        },
+    )?
+    .with_name(&data.name)?;
+    constructor.set_constructor(true);
+
+    // Like `class` syntax: methods are not enumerable, `prototype` is read-only
+    let prototype = Object::new(ctx.clone())?;
+    for (name, callable) in &data.methods {
+        let method = JsFunction::new(
+            ctx.clone(),
+            InstanceMethod {
+                class: data.name.clone(),
+                method: name.clone(),
+                callable: callable.as_ref().map(Zval::shallow_clone),
+            },
+        )?
+        .with_name(name)?;
+        prototype.prop(name.as_str(), Property::from(method).writable().configurable())?;
+    }
+    for name in &data.properties {
+        let access = |write| InstanceProperty {
+            class: data.name.clone(),
+            property: name.clone(),
+            write,
+        };
+        prototype.prop(name.as_str(), Accessor::new(access(false), access(true)).configurable())?;
+    }
+    prototype.prop("constructor", Property::from(constructor.clone()).writable().configurable())?;
+    constructor.prop("prototype", Property::from(prototype))?;
+
+    for (name, callable) in &data.static_methods {
+        let method = JsFunction::new(
+            ctx.clone(),
+            PhpCallableCallback {
+                callable: callable.shallow_clone(),
+            },
+        )?
+        .with_name(name)?;
+        constructor.prop(name.as_str(), Property::from(method).writable().configurable())?;
+    }
+    for (name, value) in &data.static_properties {
+        constructor.set(name.as_str(), zval_to_js(ctx, value)?)?;
+    }
+
+    Ok(constructor)
+}
+
+/// The PHP object behind an instance of a QuickClass
+///
+/// Dropped together with the JavaScript instance, which releases the PHP object.
+struct PhpInstance {
+    object: Zval,
+}
+
+unsafe impl<'js> JsLifetime<'js> for PhpInstance {
+    type Changed<'to> = PhpInstance;
+}
+
+impl<'js> Trace<'js> for PhpInstance {
+    fn trace<'a>(&self, _tracer: Tracer<'a, 'js>) {}
+}
+
+impl<'js> JsClass<'js> for PhpInstance {
+    const NAME: &'static str = "PhpInstance";
+
+    type Mutable = Readable;
+
+    fn constructor(_ctx: &Ctx<'js>) -> rquickjs::Result<Option<Constructor<'js>>> {
+        Ok(None)
+    }
+}
+
+/// Property of the hidden `PhpInstance` prototype listing `[php class, prototype]`
+/// pairs of the classes registered with a PHP class, per context
+const INSTANCE_CLASSES: &str = "classes";
+
+/// Let PHP objects of `php_class` become instances with `prototype`
+fn register_instance_class<'js>(ctx: &Ctx<'js>, php_class: &str, prototype: Object<'js>) -> rquickjs::Result<()> {
+    let Some(holder) = Class::<PhpInstance>::prototype(ctx)? else {
+        return Ok(());
+    };
+    let classes = match holder.get::<_, Option<Array>>(INSTANCE_CLASSES)? {
+        Some(classes) => classes,
+        None => {
+            let classes = Array::new(ctx.clone())?;
+            holder.set(INSTANCE_CLASSES, classes.clone())?;
+            classes
+        }
+    };
+
+    // Registering the PHP class again replaces the earlier JavaScript class
+    let kept = Array::new(ctx.clone())?;
+    for entry in classes.iter::<Array>() {
+        let entry = entry?;
+        if !entry.get::<String>(0)?.eq_ignore_ascii_case(php_class) {
+            kept.set(kept.len(), entry)?;
+        }
+    }
+    let entry = Array::new(ctx.clone())?;
+    entry.set(0, php_class)?;
+    entry.set(1, prototype)?;
+    kept.set(kept.len(), entry)?;
+
+    holder.set(INSTANCE_CLASSES, kept)
+}
+
+/// Wrap a PHP object as an instance of the registered class of its PHP class, `None` without one
+///
+/// A class registered for the exact PHP class wins over one for a parent class,
+/// otherwise the latest registration matching the object is used.
+pub(super) fn wrap_instance<'js>(ctx: &Ctx<'js>, zval: &Zval) -> rquickjs::Result<Option<Value<'js>>> {
+    let Some(obj) = zval.object() else {
+        return Ok(None);
+    };
+    let Some(holder) = Class::<PhpInstance>::prototype(ctx)? else {
+        return Ok(None);
+    };
+    let Some(classes) = holder.get::<_, Option<Array>>(INSTANCE_CLASSES)? else {
+        return Ok(None);
+    };
+
+    let class_name = obj.get_class_name().unwrap_or_default();
+    let mut entries = classes.iter::<Array>().collect::<rquickjs::Result<Vec<_>>>()?;
+    entries.reverse();
+
+    let mut prototype = None;
+    for entry in &entries {
+        let php_class: String = entry.get(0)?;
+        if php_class.trim_start_matches('\\').eq_ignore_ascii_case(&class_name) {
+            prototype = Some(entry.get::<Object>(1)?);
+            break;
+        }
+    }
+    if prototype.is_none() {
+        for entry in &entries {
+            let php_class: String = entry.get(0)?;
+            if ClassEntry::try_find(&php_class).is_some_and(|ce| obj.instance_of(ce)) {
+                prototype = Some(entry.get::<Object>(1)?);
+                break;
+            }
+        }
+    }
+
+    match prototype {
+        Some(prototype) => Class::instance_proto(PhpInstance { object: zval.shallow_clone() }, prototype)
+            .map(|instance| Some(instance.into_value())),
+        None => Ok(None),
+    }
+}
+
+/// The PHP object behind a QuickClass instance, `None` for other objects
+pub(super) fn backing_object(obj: &Object<'_>) -> Option<Zval> {
+    Class::<PhpInstance>::from_object(obj).map(|instance| instance.borrow().object.shallow_clone())
+}
+
+/// The PHP object behind `this`, throwing for other receivers like `Money.prototype.format.call({})`
+fn this_object<'js>(ctx: &Ctx<'js>, this: &Value<'js>, class: &str, member: &str) -> rquickjs::Result<Zval> {
+    this.as_object()
+        .and_then(backing_object)
+        .ok_or_else(|| Exception::throw_type(ctx, &format!("{}.{} called on an incompatible receiver", class, member)))
+}
+
+/// Convert the JavaScript arguments of a call to PHP
+fn php_args<'js>(ctx: &Ctx<'js>, params: &Params<'_, 'js>) -> rquickjs::Result<Vec<Zval>> {
+    (0..params.len())
+        .filter_map(|i| params.arg(i))
+        .map(|arg| js_to_zval(&arg).map_err(|e| Exception::throw_message(ctx, &e)))
+        .collect()
+}
+
+/// Call a PHP callable with the given arguments and convert the result to JavaScript
+pub(super) fn call_php<'js>(ctx: &Ctx<'js>, callable: &Zval, args: &[Zval]) -> rquickjs::Result<Zval> {
+    let callable = ZendCallable::new(callable)
+        .map_err(|e| Exception::throw_message(ctx, &format!("Invalid callable: {:?}", e)))?;
+    let arg_refs: Vec<&dyn IntoZvalDyn> = args.iter().map(|z| z as &dyn IntoZvalDyn).collect();
+    callable
+        .try_call(arg_refs)
+        .map_err(|e| Exception::throw_message(ctx, &format!("PHP callable failed: {:?}", e)))
+}
+
+/// `new Class(...)`, creating the PHP object through the constructor callable
+struct ClassConstructor {
+    class: String,
+    constructor: Zval,
+}
+
+impl<'js> IntoJsFunc<'js, (Ctx<'js>, rquickjs::function::Rest<Value<'js>>)> for ClassConstructor {
+    fn param_requirements() -> ParamRequirement {
+        ParamRequirement::any()
+    }
+
+    fn call<'a>(&self, params: Params<'a, 'js>) -> rquickjs::Result<Value<'js>> {
+        let ctx = params.ctx().clone();
+        if !params.is_constructor() {
+            return Err(Exception::throw_type(
+                &ctx,
+                &format!("Class constructor {} cannot be invoked without 'new'", self.class),
+            ));
+        }
+
+        // `this` is `new.target`, whose prototype also covers subclasses
+        let prototype: Object = params
+            .this()
+            .as_object()
+            .map(|target| target.get("prototype"))
+            .unwrap_or_else(|| params.function().get::<Object>().and_then(|f| f.get("prototype")))?;
+
+        let args = php_args(&ctx, &params)?;
+        let object = call_php(&ctx, &self.constructor, &args)?;
+        if !object.is_object() {
+            return Err(Exception::throw_type(
+                &ctx,
+                &format!("Constructor of {} must return an object", self.class),
+            ));
+        }
+
+        Class::instance_proto(PhpInstance { object }, prototype).map(|instance| instance.into_value())
+    }
+}
+
+/// An instance method, calling the PHP object's method or a callable receiving the object
+struct InstanceMethod {
+    class: String,
+    method: String,
+    callable: Option<Zval>,
+}
+
+impl<'js> IntoJsFunc<'js, (Ctx<'js>, rquickjs::function::Rest<Value<'js>>)> for InstanceMethod {
+    fn param_requirements() -> ParamRequirement {
+        ParamRequirement::any()
+    }
+
+    fn call<'a>(&self, params: Params<'a, 'js>) -> rquickjs::Result<Value<'js>> {
+        let ctx = params.ctx().clone();
+        let object = this_object(&ctx, &params.this(), &self.class, &self.method)?;
+        let args = php_args(&ctx, &params)?;
+
+        let result = match &self.callable {
+            Some(callable) => {
+                let mut all_args = vec![object];
+                all_args.extend(args);
+                call_php(&ctx, callable, &all_args)?
+            }
+            None => {
+                let mut method = ZendHashTable::new();
+                method
+                    .push(object)
+                    .map_err(|e| Exception::throw_message(&ctx, &format!("Failed to build callable: {:?}", e)))?;
+                method
+                    .push(self.method.as_str())
+                    .map_err(|e| Exception::throw_message(&ctx, &format!("Failed to build callable: {:?}", e)))?;
+                let mut callable = Zval::new();
+                callable.set_hashtable(method);
+                call_php(&ctx, &callable, &args)?
+            }
+        };
+
+        zval_to_js(&ctx, &result)
+    }
+}
+
+/// Getter or setter of an instance property backed by a PHP object property
+struct InstanceProperty {
+    class: String,
+    property: String,
+    write: bool,
+}
+
+impl<'js> IntoJsFunc<'js, (Ctx<'js>, rquickjs::function::Rest<Value<'js>>)> for InstanceProperty {
+    fn param_requirements() -> ParamRequirement {
+        ParamRequirement::any()
+    }
+
+    fn call<'a>(&self, params: Params<'a, 'js>) -> rquickjs::Result<Value<'js>> {
+        let ctx = params.ctx().clone();
+        let mut object = this_object(&ctx, &params.this(), &self.class, &self.property)?;
+        let obj = object
+            .object_mut()
+            .ok_or_else(|| Exception::throw_type(&ctx, "Instance is not backed by an object"))?;
+
+        if self.write {
+            let value = params.arg(0).unwrap_or_else(|| Value::new_undefined(ctx.clone()));
+            let value = js_to_zval(&value).map_err(|e| Exception::throw_message(&ctx, &e))?;
+            obj.set_property(&self.property, value).map_err(|e| {
+                Exception::throw_message(&ctx, &format!("Failed to set property {}.{}: {:?}", self.class, self.property, e))
+            })?;
+            return Ok(Value::new_undefined(ctx));
+        }
+
+        let value: &Zval = obj.get_property(&self.property).map_err(|e| {
+            Exception::throw_message(&ctx, &format!("Failed to get property {}.{}: {:?}", self.class, self.property, e))
+        })?;
+        zval_to_js(&ctx, value)
+    }
+}
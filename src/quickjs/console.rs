@@ -0,0 +1,350 @@
+use ext_php_rs::convert::IntoZvalDyn;
+use ext_php_rs::prelude::*;
+use ext_php_rs::types::{ZendCallable, ZendHashTable, Zval};
+use rquickjs::function::{IntoJsFunc, ParamRequirement, Params};
+use rquickjs::{Coerced, Context, Ctx, Exception, Object, Value};
+use std::cell::{Cell, RefCell};
+use std::collections::HashMap;
+use std::rc::Rc;
+use std::time::Instant;
+
+use crate::options::option_bool;
+
+/// Console methods installed on the global `console` object
+const METHODS: [&str; 8] = ["log", "info", "warn", "error", "debug", "table", "time", "timeEnd"];
+
+/// Nesting depth up to which objects and arrays are printed
+const MAX_INSPECT_DEPTH: usize = 2;
+
+/// Destination of `console.*` output
+///
+/// Messages are passed to the handler as `(string $level, string $message)` using
+/// PSR-3 level names, which matches `LoggerInterface::log()`, and appended to the
+/// buffer when capturing is enabled.
+pub(super) struct Console {
+    handler: Option<Zval>,
+    buffer: Option<RefCell<String>>,
+    timers: RefCell<HashMap<String, Instant>>,
+    /// Set while `restore()` replays setup code, its output was already written
+    muted: Cell<bool>,
+}
+
+impl Console {
+    /// Read the `console` and `console_capture` options
+    pub(super) fn from_options(options: Option<&ZendHashTable>) -> PhpResult<Rc<Self>> {
+        let handler = match options.and_then(|o| o.get("console")) {
+            None => None,
+            Some(handler) if handler.is_null() => None,
+            Some(handler) if handler.is_callable() => Some(handler.shallow_clone()),
+            Some(logger) if logger.is_object() => {
+                let mut method = ZendHashTable::new();
+                method
+                    .push(logger.shallow_clone())
+                    .map_err(|e| PhpException::default(format!("Failed to use logger: {:?}", e)))?;
+                method
+                    .push("log")
+                    .map_err(|e| PhpException::default(format!("Failed to use logger: {:?}", e)))?;
+
+                let mut callable = Zval::new();
+                callable.set_hashtable(method);
+                if !callable.is_callable() {
+                    return Err(PhpException::default(
+                        "Console logger must have a log(string $level, string $message) method".to_string(),
+                    ));
+                }
+                Some(callable)
+            }
+            Some(_) => {
+                return Err(PhpException::default(
+                    "Console handler must be callable or a PSR-3 logger".to_string(),
+                ));
+            }
+        };
+
+        let capture = option_bool(options, "console_capture", false);
+
+        Ok(Rc::new(Console {
+            handler,
+            buffer: capture.then(|| RefCell::new(String::new())),
+            timers: RefCell::new(HashMap::new()),
+            muted: Cell::new(false),
+        }))
+    }
+
+    /// Define the global `console` object in `context`
+    pub(super) fn install(self: &Rc<Self>, context: &Context) -> PhpResult<()> {
+        context.with(|ctx| {
+            let console = Object::new(ctx.clone())
+                .map_err(|e| PhpException::default(format!("Failed to create console: {:?}", e)))?;
+
+            for method in METHODS {
+                let callback = ConsoleMethod {
+                    console: self.clone(),
+                    method,
+                };
+                let func = rquickjs::Function::new(ctx.clone(), callback)
+                    .and_then(|func| func.with_name(method))
+                    .map_err(|e| PhpException::default(format!("Failed to create console.{}: {:?}", method, e)))?;
+                console
+                    .set(method, func)
+                    .map_err(|e| PhpException::default(format!("Failed to set console.{}: {:?}", method, e)))?;
+            }
+
+            ctx.globals()
+                .set("console", console)
+                .map_err(|e| PhpException::default(format!("Failed to set console: {:?}", e)))?;
+
+            Ok(())
+        })
+    }
+
+    /// Captured output, one line per message
+    pub(super) fn output(&self) -> Option<String> {
+        self.buffer.as_ref().map(|buffer| buffer.borrow().clone())
+    }
+
+    /// Empty the capture buffer
+    pub(super) fn clear(&self) {
+        if let Some(buffer) = &self.buffer {
+            buffer.borrow_mut().clear();
+        }
+    }
+
+    /// Discard output while `muted` is set
+    pub(super) fn set_muted(&self, muted: bool) {
+        self.muted.set(muted);
+    }
+
+    fn write(&self, level: &str, message: String) -> Result<(), String> {
+        if self.muted.get() {
+            return Ok(());
+        }
+
+        if let Some(buffer) = &self.buffer {
+            let mut buffer = buffer.borrow_mut();
+            for line in message.lines() {
+                buffer.push_str(&format!("[{}] {}\n", level, line));
+            }
+        }
+
+        if let Some(handler) = &self.handler {
+            let callable = ZendCallable::new(handler).map_err(|e| format!("Invalid console handler: {:?}", e))?;
+            let level = level.to_string();
+            let args: Vec<&dyn IntoZvalDyn> = vec![&level, &message];
+            callable
+                .try_call(args)
+                .map_err(|e| format!("Console handler failed: {:?}", e))?;
+        }
+
+        Ok(())
+    }
+}
+
+/// A single `console.*` method
+#[derive(Clone)]
+struct ConsoleMethod {
+    console: Rc<Console>,
+    method: &'static str,
+}
+
+impl<'js> IntoJsFunc<'js, (Ctx<'js>, rquickjs::function::Rest<Value<'js>>)> for ConsoleMethod {
+    fn param_requirements() -> ParamRequirement {
+        ParamRequirement::any()
+    }
+
+    fn call<'a>(&self, params: Params<'a, 'js>) -> rquickjs::Result<Value<'js>> {
+        let ctx = params.ctx().clone();
+        let args: Vec<Value<'js>> = (0..params.len()).filter_map(|i| params.arg(i)).collect();
+
+        let (level, message) = match self.method {
+            "log" | "info" => ("info", format_args(&args)),
+            "warn" => ("warning", format_args(&args)),
+            "error" => ("error", format_args(&args)),
+            "debug" => ("debug", format_args(&args)),
+            "table" => ("info", format_table(&args)),
+            "time" => {
+                self.console.timers.borrow_mut().insert(timer_label(&args), Instant::now());
+                return Ok(Value::new_undefined(ctx));
+            }
+            _ => {
+                let label = timer_label(&args);
+                match self.console.timers.borrow_mut().remove(&label) {
+                    Some(start) => {
+                        let elapsed = start.elapsed().as_secs_f64() * 1000.0;
+                        ("info", format!("{}: {:.3}ms", label, elapsed))
+                    }
+                    None => ("warning", format!("Timer '{}' does not exist", label)),
+                }
+            }
+        };
+
+        self.console
+            .write(level, message)
+            .map_err(|e| Exception::throw_message(&ctx, &e))?;
+
+        Ok(Value::new_undefined(ctx))
+    }
+}
+
+/// Label of `console.time()` / `console.timeEnd()`, `default` when omitted
+fn timer_label(args: &[Value]) -> String {
+    match args.first() {
+        Some(label) if !label.is_undefined() => to_display_string(label),
+        _ => "default".to_string(),
+    }
+}
+
+/// Join the arguments with spaces like browsers do
+fn format_args(args: &[Value]) -> String {
+    args.iter()
+        .map(|arg| {
+            if arg.is_string() {
+                to_display_string(arg)
+            } else {
+                inspect(arg, 0)
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Render a value for display, strings are quoted inside objects and arrays
+fn inspect(value: &Value, depth: usize) -> String {
+    if value.is_string() {
+        return format!("'{}'", to_display_string(value));
+    }
+
+    if value.is_function() {
+        let name: String = value
+            .as_object()
+            .and_then(|obj| obj.get::<_, String>("name").ok())
+            .unwrap_or_default();
+        return if name.is_empty() {
+            "[Function (anonymous)]".to_string()
+        } else {
+            format!("[Function: {}]", name)
+        };
+    }
+
+    if value.is_error() {
+        return to_display_string(value);
+    }
+
+    if let Some(array) = value.as_array() {
+        if depth >= MAX_INSPECT_DEPTH {
+            return "[Array]".to_string();
+        }
+        let items: Vec<String> = (0..array.len())
+            .map(|i| {
+                array
+                    .get::<Value>(i)
+                    .map(|item| inspect(&item, depth + 1))
+                    .unwrap_or_default()
+            })
+            .collect();
+        return if items.is_empty() {
+            "[]".to_string()
+        } else {
+            format!("[ {} ]", items.join(", "))
+        };
+    }
+
+    if let Some(obj) = value.as_object() {
+        if depth >= MAX_INSPECT_DEPTH {
+            return "[Object]".to_string();
+        }
+        let entries: Vec<String> = obj
+            .props::<String, Value>()
+            .filter_map(|prop| prop.ok())
+            .map(|(key, item)| format!("{}: {}", key, inspect(&item, depth + 1)))
+            .collect();
+        return if entries.is_empty() {
+            "{}".to_string()
+        } else {
+            format!("{{ {} }}", entries.join(", "))
+        };
+    }
+
+    to_display_string(value)
+}
+
+/// `String(value)`, falling back to the type name for values that cannot be converted
+fn to_display_string(value: &Value) -> String {
+    value
+        .get::<Coerced<String>>()
+        .map(|s| s.0)
+        .unwrap_or_else(|_| format!("[{}]", value.type_name()))
+}
+
+/// Render `console.table()` data as a plain text table
+fn format_table(args: &[Value]) -> String {
+    let data = match args.first().and_then(|arg| arg.as_object()) {
+        Some(data) => data,
+        None => return format_args(args),
+    };
+
+    let mut columns: Vec<String> = Vec::new();
+    let mut has_values = false;
+    let mut rows: Vec<(String, HashMap<String, String>, Option<String>)> = Vec::new();
+
+    for prop in data.props::<String, Value>() {
+        let (index, row) = match prop {
+            Ok(prop) => prop,
+            Err(_) => continue,
+        };
+
+        let mut cells = HashMap::new();
+        let mut value = None;
+        match row.as_object() {
+            Some(row) if !row.is_function() => {
+                for cell in row.props::<String, Value>().filter_map(|cell| cell.ok()) {
+                    if !columns.contains(&cell.0) {
+                        columns.push(cell.0.clone());
+                    }
+                    cells.insert(cell.0, inspect(&cell.1, 1));
+                }
+            }
+            _ => {
+                has_values = true;
+                value = Some(inspect(&row, 1));
+            }
+        }
+        rows.push((index, cells, value));
+    }
+
+    let mut header = vec!["(index)".to_string()];
+    header.extend(columns.iter().cloned());
+    if has_values {
+        header.push("Values".to_string());
+    }
+
+    let mut lines: Vec<Vec<String>> = vec![header];
+    for (index, cells, value) in rows {
+        let mut line = vec![index];
+        for column in &columns {
+            line.push(cells.get(column).cloned().unwrap_or_default());
+        }
+        if has_values {
+            line.push(value.unwrap_or_default());
+        }
+        lines.push(line);
+    }
+
+    let widths: Vec<usize> = (0..lines[0].len())
+        .map(|i| lines.iter().map(|line| line[i].chars().count()).max().unwrap_or(0))
+        .collect();
+
+    lines
+        .iter()
+        .map(|line| {
+            line.iter()
+                .zip(&widths)
+                .map(|(cell, width)| format!("{:width$}", cell, width = width))
+                .collect::<Vec<_>>()
+                .join(" | ")
+                .trim_end()
+                .to_string()
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
@@ -0,0 +1,236 @@
+use ext_php_rs::prelude::*;
+use rquickjs::function::{Constructor, IntoJsFunc, ParamRequirement, Params};
+use rquickjs::object::Property;
+use rquickjs::{Array, Context, Ctx, Exception, Function as JsFunction, Object, Value};
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use super::class::call_php;
+use super::{
+    build_js_object, js_to_zval, zval_to_js, PhpFunctionCallback, PropertyAttributes, QuickObjectData,
+    QuickObjectMember,
+};
+
+/// Proxy traps of a live QuickObject
+const TRAPS: [(&str, Trap); 7] = [
+    ("get", Trap::Get),
+    ("set", Trap::Set),
+    ("has", Trap::Has),
+    ("deleteProperty", Trap::DeleteProperty),
+    ("ownKeys", Trap::OwnKeys),
+    ("getOwnPropertyDescriptor", Trap::GetOwnPropertyDescriptor),
+    ("defineProperty", Trap::DefineProperty),
+];
+
+#[derive(Clone, Copy)]
+enum Trap {
+    Get,
+    Set,
+    Has,
+    DeleteProperty,
+    OwnKeys,
+    GetOwnPropertyDescriptor,
+    DefineProperty,
+}
+
+/// Define a live QuickObject as a global in the JavaScript context
+pub(super) fn context_register_live_object(
+    context: &Context,
+    js_name: &str,
+    data: &Rc<RefCell<QuickObjectData>>,
+) -> PhpResult<()> {
+    context.with(|ctx| {
+        let proxy = live_object(&ctx, data)
+            .map_err(|e| PhpException::default(format!("Failed to build live object: {:?}", e)))?;
+
+        let globals = ctx.globals();
+        let defined = if data.borrow().frozen {
+            globals.prop(js_name, Property::from(proxy).enumerable())
+        } else {
+            globals.set(js_name, proxy)
+        };
+        defined.map_err(|e| PhpException::default(format!("Failed to set global object: {:?}", e)))?;
+
+        Ok(())
+    })
+}
+
+/// A `Proxy` reading the members of `data` on every access
+///
+/// Members added or changed in PHP after registration are visible right away, and
+/// scripts writing a property update the QuickObject. Only primitive values can be
+/// written, objects would be converted and changing them afterwards would be lost,
+/// and functions, nested objects and other members registered from PHP cannot be
+/// replaced; both throw a `TypeError`. Functions and nested objects are created on
+/// access, so `App.upper !== App.upper`. Live objects cannot be frozen from
+/// JavaScript, call `freeze()` on the QuickObject instead.
+pub(super) fn live_object<'js>(ctx: &Ctx<'js>, data: &Rc<RefCell<QuickObjectData>>) -> rquickjs::Result<Value<'js>> {
+    let proxy: Constructor = ctx.globals().get("Proxy").map_err(|_| {
+        Exception::throw_message(ctx, "Live objects require the proxy intrinsic")
+    })?;
+
+    let handler = Object::new(ctx.clone())?;
+    for (name, trap) in TRAPS {
+        let callback = LiveTrap {
+            data: data.clone(),
+            trap,
+        };
+        handler.set(name, JsFunction::new(ctx.clone(), callback)?.with_name(name)?)?;
+    }
+
+    proxy.construct((Object::new(ctx.clone())?, handler))
+}
+
+/// The current JavaScript value of a member
+fn member_value<'js>(ctx: &Ctx<'js>, member: &QuickObjectMember) -> rquickjs::Result<Value<'js>> {
+    match member {
+        QuickObjectMember::Property(value) => zval_to_js(ctx, value),
+        QuickObjectMember::Function(func_name) => {
+            let callback = PhpFunctionCallback {
+                func_name: func_name.clone(),
+            };
+            Ok(JsFunction::new(ctx.clone(), callback)?.into_value())
+        }
+        QuickObjectMember::NestedObject(nested_data) => Ok(build_js_object(ctx, nested_data)?.into_value()),
+        QuickObjectMember::LiveObject(nested_data) => live_object(ctx, nested_data),
+        QuickObjectMember::Accessor(get, _) => zval_to_js(ctx, &call_php(ctx, get, &[])?),
+    }
+}
+
+/// One trap of the proxy handler
+struct LiveTrap {
+    data: Rc<RefCell<QuickObjectData>>,
+    trap: Trap,
+}
+
+impl LiveTrap {
+    /// A copy of the member `name`, so no borrow is held while PHP code runs
+    fn member(&self, name: &str) -> Option<(QuickObjectMember, PropertyAttributes)> {
+        self.data
+            .borrow()
+            .members
+            .iter()
+            .find(|(n, _, _)| n == name)
+            .map(|(_, member, attributes)| (member.clone(), *attributes))
+    }
+
+    fn set<'js>(&self, ctx: &Ctx<'js>, name: &str, value: Value<'js>) -> rquickjs::Result<bool> {
+        if self.data.borrow().frozen {
+            return Ok(false);
+        }
+
+        let attributes = match self.member(name) {
+            Some((QuickObjectMember::Accessor(_, Some(set)), _)) => {
+                let value = js_to_zval(&value).map_err(|e| Exception::throw_message(ctx, &e))?;
+                call_php(ctx, &set, &[value])?;
+                return Ok(true);
+            }
+            Some((QuickObjectMember::Accessor(_, None), _)) => return Ok(false),
+            Some((_, attributes)) if !attributes.writable => return Ok(false),
+            Some((QuickObjectMember::Property(_), attributes)) => attributes,
+            Some(_) => {
+                return Err(Exception::throw_type(
+                    ctx,
+                    &format!("Cannot replace '{}' of a live object from JavaScript", name),
+                ));
+            }
+            None => PropertyAttributes::from_array(None),
+        };
+        if value.is_object() {
+            return Err(Exception::throw_type(
+                ctx,
+                &format!("Cannot assign an object or function to '{}' of a live object, only primitive values", name),
+            ));
+        }
+
+        let value = js_to_zval(&value).map_err(|e| Exception::throw_message(ctx, &e))?;
+        let member = QuickObjectMember::Property(value);
+
+        // Update in place to keep the key order and attributes
+        let mut data = self.data.borrow_mut();
+        match data.members.iter().position(|(n, _, _)| n == name) {
+            Some(index) => data.members[index].1 = member,
+            None => data.members.push((name.to_string(), member, attributes)),
+        }
+        Ok(true)
+    }
+
+    fn delete(&self, name: &str) -> bool {
+        let mut data = self.data.borrow_mut();
+        if data.frozen {
+            return false;
+        }
+
+        let configurable = data
+            .members
+            .iter()
+            .all(|(n, _, attributes)| n != name || attributes.configurable);
+        if configurable {
+            data.members.retain(|(n, _, _)| n != name);
+        }
+        configurable
+    }
+
+    fn descriptor<'js>(
+        &self,
+        ctx: &Ctx<'js>,
+        member: &QuickObjectMember,
+        attributes: PropertyAttributes,
+    ) -> rquickjs::Result<Value<'js>> {
+        // Always configurable, proxies may not report non-configurable properties the target lacks
+        let writable = match member {
+            QuickObjectMember::Accessor(_, set) => set.is_some(),
+            _ => attributes.writable,
+        };
+        let frozen = self.data.borrow().frozen;
+
+        let descriptor = Object::new(ctx.clone())?;
+        descriptor.set("value", member_value(ctx, member)?)?;
+        descriptor.set("writable", writable && !frozen)?;
+        descriptor.set("enumerable", attributes.enumerable)?;
+        descriptor.set("configurable", true)?;
+        Ok(descriptor.into_value())
+    }
+}
+
+impl<'js> IntoJsFunc<'js, (Ctx<'js>, rquickjs::function::Rest<Value<'js>>)> for LiveTrap {
+    fn param_requirements() -> ParamRequirement {
+        ParamRequirement::any()
+    }
+
+    fn call<'a>(&self, params: Params<'a, 'js>) -> rquickjs::Result<Value<'js>> {
+        let ctx = params.ctx().clone();
+        let arg = |i| params.arg(i).unwrap_or_else(|| Value::new_undefined(ctx.clone()));
+        let target: Object = arg(0).get()?;
+        let key = arg(1);
+        // Symbols like `Symbol.iterator` are not QuickObject members
+        let name = key.as_string().map(|name| name.to_string()).transpose()?;
+        let member = name.as_deref().and_then(|name| self.member(name));
+
+        match (self.trap, name, member) {
+            (Trap::Get, _, Some((member, _))) => member_value(&ctx, &member),
+            (Trap::Get, _, None) => target.get(key),
+            (Trap::Set, Some(name), _) => Ok(Value::new_bool(ctx.clone(), self.set(&ctx, &name, arg(2))?)),
+            (Trap::Set, None, _) | (Trap::DefineProperty, _, _) => Ok(Value::new_bool(ctx.clone(), false)),
+            (Trap::Has, _, Some(_)) => Ok(Value::new_bool(ctx.clone(), true)),
+            (Trap::Has, _, None) => {
+                let reflect: Object = ctx.globals().get("Reflect")?;
+                let has: JsFunction = reflect.get("has")?;
+                has.call((target, key))
+            }
+            (Trap::DeleteProperty, Some(name), _) => Ok(Value::new_bool(ctx.clone(), self.delete(&name))),
+            (Trap::DeleteProperty, None, _) => Ok(Value::new_bool(ctx.clone(), true)),
+            (Trap::OwnKeys, _, _) => {
+                let keys = Array::new(ctx.clone())?;
+                for (i, (name, _, _)) in self.data.borrow().members.iter().enumerate() {
+                    keys.set(i, name.as_str())?;
+                }
+                Ok(keys.into_value())
+            }
+            (Trap::GetOwnPropertyDescriptor, _, Some((member, attributes))) => {
+                self.descriptor(&ctx, &member, attributes)
+            }
+            (Trap::GetOwnPropertyDescriptor, _, None) => Ok(Value::new_undefined(ctx.clone())),
+        }
+    }
+}
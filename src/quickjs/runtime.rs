@@ -0,0 +1,312 @@
+use ext_php_rs::prelude::*;
+use ext_php_rs::types::{ZendHashTable, Zval};
+use rquickjs::{Context, Runtime};
+use std::cell::{Cell, RefCell};
+use std::rc::Rc;
+use std::sync::Arc;
+
+use super::class::context_register_class;
+use super::console::Console;
+use super::live::context_register_live_object;
+use super::{
+    context_add_object_method, context_add_object_property, context_call, context_call_method,
+    context_delete_global, context_describe, context_eval, context_eval_file, context_eval_json,
+    context_freeze_globals, context_get_global, context_has_global, context_list_globals,
+    context_register_function, context_register_object, context_remove_object_member, context_set_global,
+    context_set_global_json, context_typeof, context_unregister_function, create_context,
+    intrinsics_from_options, list_args, memory_stats, ConversionOptions, QuickClass, QuickObject,
+};
+use crate::options::{option_bool, option_long};
+
+/// A QuickJS runtime that can host several isolated contexts
+///
+/// All contexts created from one runtime share its memory limit, stack size and
+/// garbage collector, but each has its own global object.
+///
+/// Example usage:
+/// ```php
+/// $runtime = new QuickJSRuntime(['memory_limit' => 32 * 1024 * 1024]);
+/// $first = $runtime->createContext();
+/// $second = $runtime->createContext(['intrinsics' => ['json']]);
+/// $first->setGlobal('name', 'first');
+/// $second->hasGlobal('name'); // false
+/// ```
+#[php_class]
+#[php(name = "Shopware\\PHPExtension\\QuickJS\\QuickJSRuntime")]
+pub struct QuickJSRuntime {
+    runtime: Arc<Runtime>,
+    conversion: ConversionOptions,
+}
+
+#[php_impl]
+impl QuickJSRuntime {
+    /// Create a new runtime
+    ///
+    /// Supported options:
+    /// - `memory_limit` (int): memory limit in bytes for all contexts of this runtime
+    /// - `max_stack_size` (int): max stack size in bytes
+    /// - `gc_threshold` (int): allocated bytes that trigger the next garbage collection
+    /// - `max_depth`, `circular`, `empty_array` and `objects`: value conversion of all contexts,
+    ///   see the QuickJS constructor
+    #[php(optional = options)]
+    pub fn __construct(options: Option<&ZendHashTable>) -> PhpResult<Self> {
+        let conversion = ConversionOptions::from_options(options)?;
+        let runtime = Runtime::new()
+            .map_err(|e| PhpException::default(format!("Failed to create runtime: {:?}", e)))?;
+
+        let memory_limit = option_long(options, "memory_limit", 0);
+        if memory_limit > 0 {
+            runtime.set_memory_limit(memory_limit as usize);
+        }
+        let max_stack_size = option_long(options, "max_stack_size", 0);
+        if max_stack_size > 0 {
+            runtime.set_max_stack_size(max_stack_size as usize);
+        }
+        let gc_threshold = option_long(options, "gc_threshold", 0);
+        if gc_threshold > 0 {
+            runtime.set_gc_threshold(gc_threshold as usize);
+        }
+
+        Ok(QuickJSRuntime {
+            runtime: Arc::new(runtime),
+            conversion,
+        })
+    }
+
+    /// Create a new context in this runtime
+    ///
+    /// Accepts the `intrinsics`, `console` and `console_capture` options of the
+    /// QuickJS constructor.
+    #[php(optional = options)]
+    pub fn create_context(&self, options: Option<&ZendHashTable>) -> PhpResult<QuickJSContext> {
+        let intrinsics = intrinsics_from_options(options)?;
+        let context = create_context(&self.runtime, intrinsics.as_deref())?;
+        self.conversion.install(&context)?;
+        let console = Console::from_options(options)?;
+        console.install(&context)?;
+
+        Ok(QuickJSContext {
+            context: RefCell::new(Some(context)),
+            console,
+            depth: Cell::new(0),
+        })
+    }
+
+    /// Set memory limit in bytes
+    pub fn set_memory_limit(&self, limit: i64) -> PhpResult<()> {
+        self.runtime.set_memory_limit(limit as usize);
+        Ok(())
+    }
+
+    /// Set max stack size in bytes
+    pub fn set_max_stack_size(&self, size: i64) -> PhpResult<()> {
+        self.runtime.set_max_stack_size(size as usize);
+        Ok(())
+    }
+
+    /// Run garbage collection
+    pub fn gc(&self) -> PhpResult<()> {
+        self.runtime.run_gc();
+        Ok(())
+    }
+
+    /// Set the number of allocated bytes that triggers the next garbage collection
+    pub fn set_gc_threshold(&self, threshold: i64) -> PhpResult<()> {
+        self.runtime.set_gc_threshold(threshold as usize);
+        Ok(())
+    }
+
+    /// Get memory usage in bytes of all contexts of this runtime
+    pub fn memory_usage(&self) -> i64 {
+        let usage = self.runtime.memory_usage();
+        usage.memory_used_size
+    }
+
+    /// Get detailed memory statistics of all contexts of this runtime
+    pub fn get_memory_stats(&self) -> Zval {
+        memory_stats(&self.runtime)
+    }
+}
+
+/// An isolated JavaScript context created by `QuickJSRuntime::createContext()`
+///
+/// Offers the same evaluation and registration methods as QuickJS. Call `dispose()`
+/// to release the context early, afterwards every method throws.
+#[php_class]
+#[php(name = "Shopware\\PHPExtension\\QuickJS\\QuickJSContext")]
+pub struct QuickJSContext {
+    context: RefCell<Option<Context>>,
+    console: Rc<Console>,
+    /// Nesting depth of calls using the context, `dispose()` is refused while above zero
+    depth: Cell<usize>,
+}
+
+#[php_impl]
+impl QuickJSContext {
+    /// Release the context and all values it holds
+    ///
+    /// Throws when called from a PHP callback while JavaScript of this context is running.
+    pub fn dispose(&self) -> PhpResult<()> {
+        if self.depth.get() > 0 {
+            return Err(PhpException::default("Cannot dispose while JavaScript is running".to_string()));
+        }
+
+        self.context.borrow_mut().take();
+        Ok(())
+    }
+
+    /// Check whether `dispose()` has been called
+    pub fn is_disposed(&self) -> bool {
+        self.context.borrow().is_none()
+    }
+
+    /// Get the collected `console.*` output, requires the `console_capture` option
+    pub fn get_console_output(&self) -> PhpResult<String> {
+        self.console.output().ok_or_else(|| {
+            PhpException::default("Console capture is disabled, create the context with ['console_capture' => true]".to_string())
+        })
+    }
+
+    /// Discard the collected console output
+    pub fn clear_console_output(&self) {
+        self.console.clear();
+    }
+
+    /// Freeze the global object so scripts can no longer add, replace or delete globals
+    pub fn freeze_globals(&self) -> PhpResult<()> {
+        self.with_context(context_freeze_globals)
+    }
+
+    /// Evaluate JavaScript code and return the result
+    pub fn eval(&self, code: &str) -> PhpResult<Zval> {
+        self.with_context(|context| context_eval(context, code))
+    }
+
+    /// Evaluate JavaScript code from a file
+    pub fn eval_file(&self, filename: &str) -> PhpResult<Zval> {
+        self.with_context(|context| context_eval_file(context, filename))
+    }
+
+    /// Set a global variable in the JavaScript context
+    pub fn set_global(&self, name: &str, value: &Zval) -> PhpResult<()> {
+        self.with_context(|context| context_set_global(context, name, value))
+    }
+
+    /// Get a global variable from the JavaScript context
+    pub fn get_global(&self, name: &str) -> PhpResult<Zval> {
+        self.with_context(|context| context_get_global(context, name))
+    }
+
+    /// Set a global variable from a JSON string, parsed natively by QuickJS
+    pub fn set_global_json(&self, name: &str, json: &str) -> PhpResult<()> {
+        self.with_context(|context| context_set_global_json(context, name, json))
+    }
+
+    /// Evaluate JavaScript code and return the result as a JSON string
+    pub fn eval_json(&self, code: &str) -> PhpResult<String> {
+        self.with_context(|context| context_eval_json(context, code))
+    }
+
+    /// Call a JavaScript function by name or dotted path, one argument per PHP argument
+    pub fn call(&self, function_name: &str, args: &[&Zval]) -> PhpResult<Zval> {
+        self.with_context(|context| context_call(context, function_name, args, None))
+    }
+
+    /// Call a JavaScript function with a list of arguments and an optional `this` path
+    #[php(optional = this_path)]
+    pub fn apply(&self, function_name: &str, args: &ZendHashTable, this_path: Option<String>) -> PhpResult<Zval> {
+        let args = list_args(args)?;
+        self.with_context(|context| context_call(context, function_name, &args, this_path.as_deref()))
+    }
+
+    /// Call a method of the object at a global dotted path with the object as `this`
+    pub fn call_method(&self, object_path: &str, method: &str, args: &ZendHashTable) -> PhpResult<Zval> {
+        let args = list_args(args)?;
+        self.with_context(|context| context_call_method(context, object_path, method, &args))
+    }
+
+    /// Check if a global variable exists
+    pub fn has_global(&self, name: &str) -> PhpResult<bool> {
+        self.with_context(|context| context_has_global(context, name))
+    }
+
+    /// Get the type of a global variable or a dotted path as a string
+    pub fn typeof_global(&self, name: &str) -> PhpResult<String> {
+        self.with_context(|context| context_typeof(context, name))
+    }
+
+    /// List the names of the enumerable global variables
+    pub fn list_globals(&self) -> PhpResult<Vec<String>> {
+        self.with_context(context_list_globals)
+    }
+
+    /// Describe the value of a global variable or a dotted path
+    pub fn describe(&self, path: &str) -> PhpResult<Zval> {
+        self.with_context(|context| context_describe(context, path))
+    }
+
+    /// Register a PHP function (by name) as a JavaScript function
+    pub fn register_function(&self, js_name: &str, php_function_name: &str) -> PhpResult<()> {
+        self.with_context(|context| context_register_function(context, js_name, php_function_name))
+    }
+
+    /// Register a global JavaScript object using a QuickObject
+    #[php(optional = options)]
+    pub fn register_object(&self, js_name: &str, obj: &QuickObject, options: Option<&ZendHashTable>) -> PhpResult<()> {
+        if option_bool(options, "live", false) {
+            let data = obj.get_live_data();
+            return self.with_context(|context| context_register_live_object(context, js_name, &data));
+        }
+        self.with_context(|context| context_register_object(context, js_name, &obj.get_data()))
+    }
+
+    /// Register a JavaScript class built with QuickClass
+    pub fn register_class(&self, class: &QuickClass) -> PhpResult<()> {
+        self.with_context(|context| context_register_class(context, &class.get_data()))
+    }
+
+    /// Create or get a global object and add a method to it
+    pub fn add_object_method(&self, object_name: &str, method_name: &str, php_function_name: &str) -> PhpResult<()> {
+        self.with_context(|context| {
+            context_add_object_method(context, object_name, method_name, php_function_name)
+        })
+    }
+
+    /// Add a property to an existing or new global object
+    pub fn add_object_property(&self, object_name: &str, property_name: &str, value: &Zval) -> PhpResult<()> {
+        self.with_context(|context| {
+            context_add_object_property(context, object_name, property_name, value)
+        })
+    }
+
+    /// Delete a global variable, function or object
+    pub fn delete_global(&self, name: &str) -> PhpResult<()> {
+        self.with_context(|context| context_delete_global(context, name))
+    }
+
+    /// Remove a function registered with `registerFunction()`
+    pub fn unregister_function(&self, js_name: &str) -> PhpResult<()> {
+        self.with_context(|context| context_unregister_function(context, js_name))
+    }
+
+    /// Remove a method or property from the object at a global dotted path
+    pub fn remove_object_member(&self, object_path: &str, member: &str) -> PhpResult<()> {
+        self.with_context(|context| context_remove_object_member(context, object_path, member))
+    }
+}
+
+impl QuickJSContext {
+    /// Run `f` with the context, failing once it has been disposed
+    fn with_context<R>(&self, f: impl FnOnce(&Context) -> PhpResult<R>) -> PhpResult<R> {
+        let context = self.context.borrow();
+        let context = context
+            .as_ref()
+            .ok_or_else(|| PhpException::default("Context has been disposed".to_string()))?;
+
+        self.depth.set(self.depth.get() + 1);
+        let result = f(context);
+        self.depth.set(self.depth.get() - 1);
+
+        result
+    }
+}
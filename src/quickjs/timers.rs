@@ -0,0 +1,270 @@
+use ext_php_rs::prelude::*;
+use rquickjs::function::{IntoJsFunc, ParamRequirement, Params, Rest};
+use rquickjs::{Context, Ctx, Exception, Function as JsFunction, Persistent, Runtime, Value};
+use std::cell::RefCell;
+use std::collections::VecDeque;
+use std::rc::Rc;
+
+/// Smallest delay of `setInterval`, so an interval cannot fire forever at one instant
+const MIN_INTERVAL_MS: f64 = 1.0;
+
+/// Most timer callbacks and microtasks one `run()` calls before giving up
+///
+/// Intervals that are never cleared and timeouts that keep rescheduling themselves
+/// never let the loop become idle, and PHP's `max_execution_time` cannot stop it.
+const MAX_CALLBACKS: usize = 100_000;
+
+/// `setTimeout`, `setInterval` and `queueMicrotask` backed by a virtual clock
+///
+/// Nothing runs on its own: `run()` fires due callbacks in order and advances the
+/// clock straight to the next timer instead of waiting, which keeps scripts
+/// deterministic and fast.
+pub(super) struct Timers {
+    state: RefCell<TimerState>,
+}
+
+#[derive(Default)]
+struct TimerState {
+    /// Virtual time in milliseconds
+    now: f64,
+    next_id: i32,
+    timers: Vec<Timer>,
+    microtasks: VecDeque<Persistent<JsFunction<'static>>>,
+}
+
+struct Timer {
+    id: i32,
+    due: f64,
+    interval: Option<f64>,
+    callback: Persistent<JsFunction<'static>>,
+    args: Vec<Persistent<Value<'static>>>,
+}
+
+#[derive(Clone, Copy)]
+enum TimerKind {
+    Timeout,
+    Interval,
+    Clear,
+    Microtask,
+}
+
+impl Timers {
+    pub(super) fn new() -> Rc<Self> {
+        Rc::new(Timers {
+            state: RefCell::new(TimerState::default()),
+        })
+    }
+
+    /// Define the timer functions as globals in `context`
+    pub(super) fn install(self: &Rc<Self>, context: &Context) -> PhpResult<()> {
+        let functions = [
+            ("setTimeout", TimerKind::Timeout),
+            ("setInterval", TimerKind::Interval),
+            ("clearTimeout", TimerKind::Clear),
+            ("clearInterval", TimerKind::Clear),
+            ("queueMicrotask", TimerKind::Microtask),
+        ];
+
+        context.with(|ctx| {
+            let globals = ctx.globals();
+            for (name, kind) in functions {
+                let callback = TimerFunction {
+                    timers: self.clone(),
+                    kind,
+                };
+                let func = rquickjs::Function::new(ctx.clone(), callback)
+                    .and_then(|func| func.with_name(name))
+                    .map_err(|e| PhpException::default(format!("Failed to create {}: {:?}", name, e)))?;
+                globals
+                    .set(name, func)
+                    .map_err(|e| PhpException::default(format!("Failed to set {}: {:?}", name, e)))?;
+            }
+
+            Ok(())
+        })
+    }
+
+    /// Drop all pending callbacks and reset the clock
+    ///
+    /// Must be called before the context the callbacks belong to is dropped.
+    pub(super) fn clear(&self) {
+        *self.state.borrow_mut() = TimerState::default();
+    }
+
+    /// Run microtasks, promise jobs and timers until idle or `max_ms` of virtual time passed
+    ///
+    /// Fails after `MAX_CALLBACKS` callbacks instead of looping forever. Returns
+    /// whether timers are still pending.
+    pub(super) fn run(&self, runtime: &Runtime, context: &Context, max_ms: Option<i64>) -> PhpResult<bool> {
+        let deadline = max_ms.map(|ms| self.state.borrow().now + ms.max(0) as f64);
+        let mut budget = MAX_CALLBACKS;
+
+        loop {
+            self.run_jobs(runtime, context, &mut budget)?;
+
+            let timer = {
+                let mut state = self.state.borrow_mut();
+                let next = state
+                    .timers
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, timer)| deadline.is_none_or(|deadline| timer.due <= deadline))
+                    .min_by(|(_, a), (_, b)| a.due.total_cmp(&b.due).then(a.id.cmp(&b.id)))
+                    .map(|(index, _)| index);
+
+                match next {
+                    Some(index) => {
+                        spend(&mut budget)?;
+                        let timer = state.timers.remove(index);
+                        state.now = state.now.max(timer.due);
+
+                        // Reschedule before calling, so the callback can clear its own interval
+                        if let Some(interval) = timer.interval {
+                            let due = state.now + interval;
+                            state.timers.push(Timer {
+                                id: timer.id,
+                                due,
+                                interval: timer.interval,
+                                callback: timer.callback.clone(),
+                                args: timer.args.clone(),
+                            });
+                        }
+                        timer
+                    }
+                    None => break,
+                }
+            };
+
+            call_persistent(context, timer.callback, timer.args)?;
+        }
+
+        let mut state = self.state.borrow_mut();
+        if let Some(deadline) = deadline {
+            state.now = state.now.max(deadline);
+        }
+
+        Ok(!state.timers.is_empty())
+    }
+
+    /// Run queued microtasks and promise jobs until both queues are empty
+    ///
+    /// Microtasks are counted before they run and promise jobs after, so the budget
+    /// running out never drops a queued callback.
+    fn run_jobs(&self, runtime: &Runtime, context: &Context, budget: &mut usize) -> PhpResult<()> {
+        loop {
+            if !self.state.borrow().microtasks.is_empty() {
+                spend(budget)?;
+            }
+            let microtask = self.state.borrow_mut().microtasks.pop_front();
+            if let Some(callback) = microtask {
+                call_persistent(context, callback, Vec::new())?;
+                continue;
+            }
+
+            match runtime.execute_pending_job() {
+                Ok(true) => spend(budget)?,
+                Ok(false) => return Ok(()),
+                Err(e) => {
+                    return Err(PhpException::default(format!("JavaScript error in pending job: {:?}", e)));
+                }
+            }
+        }
+    }
+}
+
+/// Count one callback against the budget of a `run()`
+fn spend(budget: &mut usize) -> PhpResult<()> {
+    if *budget == 0 {
+        return Err(PhpException::default(format!(
+            "Event loop did not become idle after {} callbacks, clear intervals or pass $maxMs",
+            MAX_CALLBACKS
+        )));
+    }
+
+    *budget -= 1;
+    Ok(())
+}
+
+/// Call a stored callback with stored arguments
+fn call_persistent(
+    context: &Context,
+    callback: Persistent<JsFunction<'static>>,
+    args: Vec<Persistent<Value<'static>>>,
+) -> PhpResult<()> {
+    context.with(|ctx| {
+        let callback = callback
+            .restore(&ctx)
+            .map_err(|e| PhpException::default(format!("Failed to restore callback: {:?}", e)))?;
+        let args = args
+            .into_iter()
+            .map(|arg| arg.restore(&ctx))
+            .collect::<rquickjs::Result<Vec<Value>>>()
+            .map_err(|e| PhpException::default(format!("Failed to restore callback arguments: {:?}", e)))?;
+
+        callback
+            .call::<_, Value>((Rest(args),))
+            .map_err(|e| PhpException::default(format!("JavaScript error in timer callback: {:?}", e)))?;
+
+        Ok(())
+    })
+}
+
+/// One of the global timer functions
+#[derive(Clone)]
+struct TimerFunction {
+    timers: Rc<Timers>,
+    kind: TimerKind,
+}
+
+impl<'js> IntoJsFunc<'js, (Ctx<'js>, Rest<Value<'js>>)> for TimerFunction {
+    fn param_requirements() -> ParamRequirement {
+        ParamRequirement::any()
+    }
+
+    fn call<'a>(&self, params: Params<'a, 'js>) -> rquickjs::Result<Value<'js>> {
+        let ctx = params.ctx().clone();
+        let mut args: VecDeque<Value<'js>> = (0..params.len()).filter_map(|i| params.arg(i)).collect();
+        let first = args.pop_front();
+
+        if let TimerKind::Clear = self.kind {
+            if let Some(id) = first.and_then(|id| id.as_number()) {
+                self.timers.state.borrow_mut().timers.retain(|timer| timer.id as f64 != id);
+            }
+            return Ok(Value::new_undefined(ctx));
+        }
+
+        let callback = match first.and_then(|value| value.into_function()) {
+            Some(callback) => Persistent::save(&ctx, callback),
+            None => return Err(Exception::throw_type(&ctx, "Callback must be a function")),
+        };
+
+        let mut state = self.timers.state.borrow_mut();
+        if let TimerKind::Microtask = self.kind {
+            state.microtasks.push_back(callback);
+            return Ok(Value::new_undefined(ctx));
+        }
+
+        let delay = args
+            .pop_front()
+            .and_then(|delay| delay.as_number())
+            .filter(|delay| delay.is_finite() && *delay > 0.0)
+            .unwrap_or(0.0);
+        let interval = match self.kind {
+            TimerKind::Interval => Some(delay.max(MIN_INTERVAL_MS)),
+            _ => None,
+        };
+
+        state.next_id += 1;
+        let id = state.next_id;
+        let due = state.now + interval.unwrap_or(delay);
+        state.timers.push(Timer {
+            id,
+            due,
+            interval,
+            callback,
+            args: args.into_iter().map(|arg| Persistent::save(&ctx, arg)).collect(),
+        });
+
+        Ok(Value::new_int(ctx, id))
+    }
+}
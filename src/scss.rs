@@ -0,0 +1,514 @@
+use ext_php_rs::convert::IntoZvalDyn;
+use ext_php_rs::prelude::*;
+use ext_php_rs::types::{ArrayKey, ZendCallable, ZendHashTable, Zval};
+use grass::{Fs, InputSyntax, Options, OutputStyle};
+use std::cell::RefCell;
+use std::collections::{HashMap, HashSet};
+use std::io;
+use std::path::{Path, PathBuf};
+
+use crate::lightningcss::LightningCSS;
+use crate::options::{option_bool, option_str};
+
+/// Directory the sources returned by importers are served from, never a real path
+const IMPORTER_ROOT: &str = "importer:";
+
+/// Decorations grass adds to an import URL when probing for the file
+const PROBE_SUFFIXES: [&str; 6] = [".import.sass", ".import.scss", ".import.css", ".sass", ".scss", ".css"];
+
+/// ScssCompiler - Compiles SCSS to CSS
+///
+/// Example usage:
+/// ```php
+/// $scss = new ScssCompiler();
+/// $scss->addImportPath('/var/www/theme/scss');
+/// $scss->setVariables(['sw-color-brand-primary' => '#008490']);
+/// $css = $scss->compile('@import "base"; body { color: $sw-color-brand-primary; }');
+/// ```
+#[php_class]
+#[php(name = "Shopware\\PHPExtension\\Scss\\ScssCompiler")]
+pub struct ScssCompiler {
+    import_paths: Vec<PathBuf>,
+    variables: Vec<(String, String)>,
+    importers: Vec<Zval>,
+}
+
+#[php_impl]
+impl ScssCompiler {
+    /// Create a new ScssCompiler instance
+    pub fn __construct() -> Self {
+        ScssCompiler {
+            import_paths: Vec::new(),
+            variables: Vec::new(),
+            importers: Vec::new(),
+        }
+    }
+
+    /// Add a directory that `@import` and `@use` resolve against
+    pub fn add_import_path(&mut self, path: &str) {
+        self.import_paths.push(PathBuf::from(path));
+    }
+
+    /// Replace all import paths
+    pub fn set_import_paths(&mut self, paths: &ZendHashTable) -> PhpResult<()> {
+        let mut import_paths = Vec::new();
+        for (_, path) in paths.iter() {
+            let path = path
+                .string()
+                .ok_or_else(|| PhpException::default("Import paths must be strings".to_string()))?;
+            import_paths.push(PathBuf::from(path));
+        }
+
+        self.import_paths = import_paths;
+        Ok(())
+    }
+
+    /// Set variables that are declared before the compiled source
+    ///
+    /// Keys are variable names with or without the leading `$`. String values are
+    /// inserted as raw SCSS, so `'#008490'` and `'1rem'` keep their type.
+    ///
+    /// Example:
+    /// ```php
+    /// $scss->setVariables([
+    ///     'sw-color-brand-primary' => '#008490',
+    ///     'sw-border-radius-default' => '3px',
+    ///     'enable-shadows' => true,
+    /// ]);
+    /// ```
+    pub fn set_variables(&mut self, variables: &ZendHashTable) -> PhpResult<()> {
+        let mut declared = Vec::new();
+        for (key, value) in variables.iter() {
+            let name = match key {
+                ArrayKey::String(s) => s.trim_start_matches('$').to_string(),
+                ArrayKey::Str(s) => s.trim_start_matches('$').to_string(),
+                ArrayKey::Long(_) => String::new(),
+            };
+            if name.is_empty() {
+                return Err(PhpException::default("Variable names must be non-empty strings".to_string()));
+            }
+
+            declared.push((name.clone(), scss_value(&name, value)?));
+        }
+
+        self.variables = declared;
+        Ok(())
+    }
+
+    /// Register a PHP callable that resolves imports before the file system
+    ///
+    /// The callable receives each URL as written in `@import`, `@use` or `@forward`
+    /// (e.g. `theme/variables`) once per compile and returns the source, or null to
+    /// let the next importer or the file system handle it. Sources are parsed as SCSS
+    /// unless the URL ends in `.sass` or `.css`. Plain CSS imports, `sass:` modules and
+    /// URLs built with interpolation are not passed to importers.
+    ///
+    /// Example:
+    /// ```php
+    /// $scss->addImporter(fn (string $url) => $pluginFiles[$url] ?? null);
+    /// ```
+    pub fn add_importer(&mut self, importer: &Zval) -> PhpResult<()> {
+        if !importer.is_callable() {
+            return Err(PhpException::default("Importer must be callable".to_string()));
+        }
+
+        self.importers.push(importer.shallow_clone());
+        Ok(())
+    }
+
+    /// Compile SCSS source code to CSS
+    ///
+    /// Supported options:
+    /// - `style` (string, default `expanded`): `expanded` or `compressed`
+    /// - `syntax` (string, default `scss`): `scss`, `sass` or `css`
+    ///
+    /// Source maps are not implemented yet: grass cannot emit them, so the `source_map`
+    /// option throws instead of returning CSS without a map.
+    #[php(optional = options)]
+    pub fn compile(&self, scss: &str, options: Option<&ZendHashTable>) -> PhpResult<String> {
+        self.run(scss, options, None)
+    }
+
+    /// Compile an SCSS file to CSS, imports are resolved relative to the file first
+    ///
+    /// Without a `syntax` option the syntax follows the file extension, `.sass` files
+    /// use the indented syntax and `.css` files plain CSS.
+    #[php(optional = options)]
+    pub fn compile_file(&self, filename: &str, options: Option<&ZendHashTable>) -> PhpResult<String> {
+        let scss = std::fs::read_to_string(filename)
+            .map_err(|e| PhpException::default(format!("Failed to read file: {:?}", e)))?;
+
+        self.run(&scss, options, Some(Path::new(filename)))
+    }
+
+    /// Compile SCSS and minify the result with a LightningCSS instance in one call
+    ///
+    /// `options` takes the `compile` options like `syntax` together with the `minify`
+    /// options of LightningCSS. The browser targets and cache of the LightningCSS
+    /// instance are applied to the compiled CSS.
+    ///
+    /// Example:
+    /// ```php
+    /// $css = new LightningCSS();
+    /// $css->setBrowserTargets(['safari' => 14]);
+    /// $minified = $scss->compileAndMinify($themeScss, $css, ['hoist_license_comments' => true]);
+    /// ```
+    #[php(optional = options)]
+    pub fn compile_and_minify(
+        &self,
+        scss: &str,
+        css: &LightningCSS,
+        options: Option<&ZendHashTable>,
+    ) -> PhpResult<String> {
+        let compiled = self.compile(scss, options)?;
+        css.minify(&compiled, options)
+    }
+}
+
+impl ScssCompiler {
+    /// Prepend the variable declarations to the source in its syntax
+    ///
+    /// The indented syntax does not allow the trailing `;`, plain CSS has no variables.
+    fn with_variables(&self, scss: &str, syntax: InputSyntax) -> String {
+        let mut input = String::new();
+        if syntax != InputSyntax::Css {
+            let terminator = if syntax == InputSyntax::Sass { "" } else { ";" };
+            for (name, value) in &self.variables {
+                input.push_str(&format!("${}: {}{}\n", name, value, terminator));
+            }
+        }
+        input.push_str(scss);
+        input
+    }
+
+    /// Build the grass options and compile `scss`, surfacing importer errors first
+    ///
+    /// The directory of `file` is searched before the import paths, and its extension
+    /// picks the syntax when there is no `syntax` option.
+    fn run(&self, scss: &str, options: Option<&ZendHashTable>, file: Option<&Path>) -> PhpResult<String> {
+        if option_bool(options, "source_map", false) {
+            return Err(PhpException::default(
+                "Source maps are not implemented yet for ScssCompiler".to_string(),
+            ));
+        }
+
+        let style = match option_str(options, "style").as_deref() {
+            None | Some("expanded") => OutputStyle::Expanded,
+            Some("compressed") => OutputStyle::Compressed,
+            Some(other) => {
+                return Err(PhpException::default(format!("Unknown output style '{}'", other)));
+            }
+        };
+
+        let syntax = match option_str(options, "syntax").as_deref() {
+            None => file.map_or(InputSyntax::Scss, syntax_for_path),
+            Some("scss") => InputSyntax::Scss,
+            Some("sass") => InputSyntax::Sass,
+            Some("css") => InputSyntax::Css,
+            Some(other) => {
+                return Err(PhpException::default(format!("Unknown input syntax '{}'", other)));
+            }
+        };
+
+        let mut load_paths: Vec<PathBuf> = file.and_then(Path::parent).map(Path::to_path_buf).into_iter().collect();
+        load_paths.extend(self.import_paths.iter().cloned());
+
+        let input = self.with_variables(scss, syntax);
+        let fs = ImporterFs::new(&self.importers, &load_paths);
+        fs.resolve(&input).map_err(PhpException::default)?;
+
+        let grass_options = Options::default()
+            .fs(&fs)
+            .style(style)
+            .input_syntax(syntax)
+            .load_paths(&load_paths);
+
+        let result = grass::from_string(input, &grass_options);
+
+        if let Some(error) = fs.error.borrow_mut().take() {
+            return Err(PhpException::default(error));
+        }
+
+        result.map_err(|e| PhpException::default(format!("SCSS compile error: {}", e)))
+    }
+}
+
+/// File system for grass that serves the sources returned by the PHP importers
+///
+/// Every stylesheet read is scanned for its imports, and the importers are asked once
+/// per URL. grass then probes candidate paths for each import (partials, extensions,
+/// load paths); a probe that is a resolved URL joined to a directory grass resolves
+/// against is served from the importer result, every other path from disk.
+struct ImporterFs<'a> {
+    importers: &'a [Zval],
+    /// URLs the importers have been asked for
+    asked: RefCell<HashSet<String>>,
+    /// Import URLs resolved by an importer, mapped to the path they are served as
+    urls: RefCell<HashMap<PathBuf, PathBuf>>,
+    /// Sources returned by the importers by the path they are served as
+    files: RefCell<HashMap<PathBuf, Vec<u8>>>,
+    /// Directories relative imports resolve against: the load paths and the
+    /// directories of the stylesheets read so far
+    bases: RefCell<Vec<PathBuf>>,
+    error: RefCell<Option<String>>,
+}
+
+impl std::fmt::Debug for ImporterFs<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ImporterFs")
+            .field("importers", &self.importers.len())
+            .finish()
+    }
+}
+
+impl<'a> ImporterFs<'a> {
+    fn new(importers: &'a [Zval], load_paths: &[PathBuf]) -> Self {
+        let mut bases = vec![PathBuf::new()];
+        bases.extend(load_paths.iter().cloned());
+
+        ImporterFs {
+            importers,
+            asked: RefCell::new(HashSet::new()),
+            urls: RefCell::new(HashMap::new()),
+            files: RefCell::new(HashMap::new()),
+            bases: RefCell::new(bases),
+            error: RefCell::new(None),
+        }
+    }
+
+    /// Ask the importers for the URLs imported by `source` that were not asked for yet
+    fn resolve(&self, source: &str) -> Result<(), String> {
+        if self.importers.is_empty() {
+            return Ok(());
+        }
+
+        for url in import_urls(source) {
+            if !self.asked.borrow_mut().insert(url.clone()) {
+                continue;
+            }
+
+            if let Some(contents) = call_importers(self.importers, &url)? {
+                let mut path = Path::new(IMPORTER_ROOT).join(url.trim_start_matches('/'));
+                if !matches!(path.extension().and_then(|ext| ext.to_str()), Some("scss" | "sass" | "css")) {
+                    path.as_mut_os_string().push(".scss");
+                }
+                self.files.borrow_mut().insert(path.clone(), contents.into_bytes());
+                self.urls.borrow_mut().insert(PathBuf::from(url), path);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// The importer source a probed path resolves to, if any
+    fn served(&self, path: &Path) -> Option<PathBuf> {
+        if self.files.borrow().contains_key(path) {
+            return Some(path.to_path_buf());
+        }
+
+        let dir = path.parent().unwrap_or_else(|| Path::new(""));
+        let name = path.file_name()?.to_str()?;
+        let urls = self.urls.borrow();
+        let bases = self.bases.borrow();
+
+        [Some(name), name.strip_prefix('_')]
+            .into_iter()
+            .flatten()
+            .flat_map(|name| {
+                std::iter::once(name).chain(PROBE_SUFFIXES.iter().filter_map(|suffix| name.strip_suffix(suffix)))
+            })
+            .map(|stem| dir.join(stem))
+            .find_map(|stem| {
+                bases
+                    .iter()
+                    .filter_map(|base| stem.strip_prefix(base).ok())
+                    .find_map(|url| urls.get(url))
+                    .cloned()
+            })
+    }
+
+    /// Remember the directory of a read stylesheet, its relative imports resolve against it
+    fn add_base(&self, path: &Path) {
+        let dir = path.parent().unwrap_or_else(|| Path::new(""));
+        let mut bases = self.bases.borrow_mut();
+        if !bases.iter().any(|base| base == dir) {
+            bases.push(dir.to_path_buf());
+        }
+    }
+}
+
+impl Fs for ImporterFs<'_> {
+    fn is_dir(&self, path: &Path) -> bool {
+        path.is_dir()
+    }
+
+    fn is_file(&self, path: &Path) -> bool {
+        self.served(path).is_some() || path.is_file()
+    }
+
+    fn read(&self, path: &Path) -> io::Result<Vec<u8>> {
+        let served = self.files.borrow().get(path).cloned();
+        let contents = match served {
+            Some(contents) => contents,
+            None => std::fs::read(path)?,
+        };
+
+        self.add_base(path);
+        if let Err(error) = self.resolve(&String::from_utf8_lossy(&contents)) {
+            *self.error.borrow_mut() = Some(error.clone());
+            return Err(io::Error::other(error));
+        }
+        Ok(contents)
+    }
+
+    fn canonicalize(&self, path: &Path) -> io::Result<PathBuf> {
+        if let Some(served) = self.served(path) {
+            return Ok(served);
+        }
+        std::fs::canonicalize(path)
+    }
+}
+
+/// Ask the importers for `url` in order, returning the first source
+fn call_importers(importers: &[Zval], url: &str) -> Result<Option<String>, String> {
+    for importer in importers {
+        let callable = ZendCallable::new(importer).map_err(|e| format!("Invalid importer: {:?}", e))?;
+        let args: Vec<&dyn IntoZvalDyn> = vec![&url];
+        let contents = callable
+            .try_call(args)
+            .map_err(|e| format!("Importer failed for '{}': {:?}", url, e))?;
+
+        if contents.is_null() {
+            continue;
+        }
+        return match contents.string() {
+            Some(contents) => Ok(Some(contents)),
+            None => Err(format!("Importer must return a string or null for '{}'", url)),
+        };
+    }
+
+    Ok(None)
+}
+
+/// Collect the URLs of the `@import`, `@use` and `@forward` rules in source order
+///
+/// Comments and strings are skipped. Plain CSS imports, `sass:` modules and URLs with
+/// interpolation cannot be resolved by an importer and are left out.
+fn import_urls(source: &str) -> Vec<String> {
+    let mut urls = Vec::new();
+    let bytes = source.as_bytes();
+    let mut i = 0;
+
+    while i < bytes.len() {
+        match bytes[i] {
+            quote @ (b'"' | b'\'') => {
+                i += 1;
+                while i < bytes.len() && bytes[i] != quote {
+                    if bytes[i] == b'\\' {
+                        i += 1;
+                    }
+                    i += 1;
+                }
+                i += 1;
+            }
+            b'/' if bytes.get(i + 1) == Some(&b'/') => {
+                i = source[i..].find('\n').map(|pos| i + pos).unwrap_or(source.len());
+            }
+            b'/' if bytes.get(i + 1) == Some(&b'*') => {
+                i = source[i + 2..].find("*/").map(|pos| i + 2 + pos + 2).unwrap_or(source.len());
+            }
+            b'@' => {
+                let start = i + 1;
+                let end = source[start..]
+                    .find(|c: char| !(c.is_ascii_alphanumeric() || c == '-'))
+                    .map(|pos| start + pos)
+                    .unwrap_or(source.len());
+                let multiple = match &source[start..end] {
+                    "import" => true,
+                    "use" | "forward" => false,
+                    _ => {
+                        i = end;
+                        continue;
+                    }
+                };
+
+                i = end;
+                loop {
+                    i += source[i..].len() - source[i..].trim_start().len();
+                    let (url, next) = match bytes.get(i) {
+                        Some(&quote @ (b'"' | b'\'')) => {
+                            let end = source[i + 1..].find(quote as char).map(|pos| i + 1 + pos).unwrap_or(source.len());
+                            (&source[(i + 1).min(end)..end], end + 1)
+                        }
+                        Some(_) if !source[i..].starts_with("url(") => {
+                            let end = source[i..]
+                                .find(|c: char| c.is_whitespace() || c == ',' || c == ';')
+                                .map(|pos| i + pos)
+                                .unwrap_or(source.len());
+                            (&source[i..end], end)
+                        }
+                        _ => break,
+                    };
+
+                    if !(url.is_empty()
+                        || url.contains("#{")
+                        || url.starts_with("sass:")
+                        || url.ends_with(".css")
+                        || url.contains("://")
+                        || url.starts_with("//"))
+                    {
+                        urls.push(url.to_string());
+                    }
+
+                    i = next.min(source.len());
+                    let rest = source[i..].trim_start();
+                    if !(multiple && rest.starts_with(',')) {
+                        break;
+                    }
+                    i = source.len() - rest.len() + 1;
+                }
+            }
+            _ => i += 1,
+        }
+    }
+
+    urls
+}
+
+/// The syntax of a file by its extension, SCSS unless it is `.sass` or `.css`
+fn syntax_for_path(path: &Path) -> InputSyntax {
+    let extension = path
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .map(str::to_ascii_lowercase);
+
+    match extension.as_deref() {
+        Some("sass") => InputSyntax::Sass,
+        Some("css") => InputSyntax::Css,
+        _ => InputSyntax::Scss,
+    }
+}
+
+/// Convert a PHP value into the SCSS source of a variable value
+fn scss_value(name: &str, value: &Zval) -> PhpResult<String> {
+    if value.is_null() {
+        Ok("null".to_string())
+    } else if let Some(b) = value.bool() {
+        Ok(b.to_string())
+    } else if let Some(n) = value.long() {
+        Ok(n.to_string())
+    } else if let Some(n) = value.double() {
+        Ok(n.to_string())
+    } else if let Some(s) = value.string() {
+        if s.is_empty() {
+            Ok("\"\"".to_string())
+        } else {
+            Ok(s)
+        }
+    } else {
+        Err(PhpException::default(format!(
+            "Unsupported value for variable '${}', expected a scalar or null",
+            name
+        )))
+    }
+}
@@ -0,0 +1,125 @@
+<?php
+
+declare(strict_types=1);
+
+namespace Shopware\PHPExtension\Tests;
+
+use PHPUnit\Framework\TestCase;
+use Shopware\PHPExtension\JsMinifier;
+use Shopware\PHPExtension\QuickJS\QuickJS;
+
+class JsMinifierTest extends TestCase
+{
+    private JsMinifier $minifier;
+
+    protected function setUp(): void
+    {
+        $this->minifier = new JsMinifier();
+    }
+
+    public function testCanCreateInstance(): void
+    {
+        $this->assertInstanceOf(JsMinifier::class, $this->minifier);
+    }
+
+    public function testMinifyRemovesWhitespace(): void
+    {
+        $input = "
+            function add(first, second) {
+                return first + second;
+            }
+        ";
+        $result = $this->minifier->minify($input);
+
+        $this->assertStringNotContainsString("\n    ", $result);
+        $this->assertLessThan(strlen($input), strlen($result));
+    }
+
+    public function testMinifyMangleShortensLocalNames(): void
+    {
+        $input = 'function outer() { var someLongLocalName = 1; return someLongLocalName + 1; } outer();';
+
+        $mangled = $this->minifier->minify($input, ['compress' => false]);
+        $unmangled = $this->minifier->minify($input, ['compress' => false, 'mangle' => false]);
+
+        $this->assertStringNotContainsString('someLongLocalName', $mangled);
+        $this->assertStringContainsString('someLongLocalName', $unmangled);
+        $this->assertStringContainsString('outer', $mangled);
+    }
+
+    public function testMinifiedCodeStillWorks(): void
+    {
+        $input = 'function greet(name) { var prefix = "Hello, "; return prefix + name + "!"; }';
+        $result = $this->minifier->minify($input);
+
+        $js = new QuickJS();
+        $js->eval($result);
+        $this->assertSame('Hello, World!', $js->eval('greet("World")'));
+    }
+
+    public function testMinifyDropConsole(): void
+    {
+        $input = 'function run() { console.log("debug"); return 1; } run();';
+
+        $this->assertStringContainsString('console', $this->minifier->minify($input));
+        $this->assertStringNotContainsString('console', $this->minifier->minify($input, ['drop_console' => true]));
+    }
+
+    public function testMinifyKeepsLicenseComments(): void
+    {
+        $input = "/*! Plugin v1.0 | MIT */\nfunction run() { return 1; }";
+
+        $this->assertStringContainsString('/*! Plugin v1.0 | MIT */', $this->minifier->minify($input));
+        $this->assertStringNotContainsString('Plugin v1.0', $this->minifier->minify($input, ['license_comments' => 'none']));
+    }
+
+    public function testMinifyWithSourceMap(): void
+    {
+        $result = $this->minifier->minify('function add(a, b) { return a + b; }', [
+            'source_map' => true,
+            'filename' => 'plugin.js',
+        ]);
+
+        $this->assertIsArray($result);
+        $this->assertArrayHasKey('code', $result);
+        $map = json_decode($result['map'], true);
+        $this->assertSame(3, $map['version']);
+        $this->assertContains('plugin.js', $map['sources']);
+    }
+
+    public function testMinifyModule(): void
+    {
+        $result = $this->minifier->minify('export const answer = 42;', ['module' => true]);
+
+        $this->assertStringContainsString('export', $result);
+    }
+
+    public function testTargetLimitsSyntaxIntroducedByCompressor(): void
+    {
+        $code = 'x = Math.pow(a, b); if (y) { y = z; }';
+
+        $this->assertSame('x=a**+b,y&&=z;', $this->minifier->minify($code));
+        $this->assertSame('x=Math.pow(a,b),y&&(y=z);', $this->minifier->minify($code, ['target' => 'es2015']));
+    }
+
+    public function testTargetDoesNotTranspileInput(): void
+    {
+        $result = $this->minifier->minify('const f = async () => a ?? b; f();', ['target' => 'es2015']);
+
+        $this->assertSame('const f=async()=>a??b;f();', $result);
+    }
+
+    public function testMinifyInvalidTargetThrows(): void
+    {
+        $this->expectException(\Exception::class);
+
+        $this->minifier->minify('var a = 1;', ['target' => 'es1999']);
+    }
+
+    public function testMinifySyntaxErrorThrows(): void
+    {
+        $this->expectException(\Exception::class);
+
+        $this->minifier->minify('function (');
+    }
+}
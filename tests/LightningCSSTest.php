@@ -16,6 +16,12 @@ class LightningCSSTest extends TestCase
         $this->css = new LightningCSS();
     }
 
+    protected function tearDown(): void
+    {
+        LightningCSS::clearCache();
+        LightningCSS::setCacheCapacity(256);
+    }
+
     public function testCanCreateInstance(): void
     {
         $this->assertInstanceOf(LightningCSS::class, $this->css);
@@ -93,6 +99,75 @@ class LightningCSSTest extends TestCase
         $this->assertStringContainsString('margin', $result);
     }
 
+    public function testFormatWithCustomIndent(): void
+    {
+        $result = $this->css->format('@media screen { body { color: red; } }', ['indent' => 4]);
+
+        $this->assertStringContainsString("\n    body {", $result);
+        $this->assertStringContainsString("\n        color: red;", $result);
+    }
+
+    public function testFormatWithCustomIndentKeepsLicenseCommentBody(): void
+    {
+        $input = "/*!\n * Bootstrap v5\n * Licensed MIT\n */\n@media screen { body { color: red; } }";
+
+        foreach ([0, 3] as $indent) {
+            $result = $this->css->format($input, ['indent' => $indent, 'comments' => true]);
+
+            $this->assertStringStartsWith("/*!\n * Bootstrap v5\n * Licensed MIT\n */\n", $result);
+            $this->assertStringContainsString("\n" . str_repeat(' ', $indent) . 'body {', $result);
+            $this->assertStringContainsString("\n" . str_repeat(' ', 2 * $indent) . 'color: red;', $result);
+        }
+    }
+
+    public function testFormatWithCustomIndentKeepsAlignmentAndStrings(): void
+    {
+        $result = $this->css->format(
+            '.grid { grid-template-areas: "head head" "nav main"; content: "a  {"; } .b { color: red; }',
+            ['indent' => 4]
+        );
+
+        $this->assertStringContainsString(
+            "\n    grid-template-areas: \"head head\"\n                         \"nav main\";",
+            $result
+        );
+        $this->assertStringContainsString('content: "a  {";', $result);
+        $this->assertStringContainsString(".b {\n    color: red;\n}", $result);
+    }
+
+    public function testFormatKeepsLicenseCommentsByDefault(): void
+    {
+        $result = $this->css->format('/*! Bootstrap v5 */ body { color: red; }');
+
+        $this->assertStringContainsString('/*! Bootstrap v5 */', $result);
+    }
+
+    public function testFormatCanDropLicenseComments(): void
+    {
+        $result = $this->css->format('/*! Bootstrap v5 */ body { color: red; }', ['comments' => false]);
+
+        $this->assertStringNotContainsString('Bootstrap', $result);
+    }
+
+    public function testFormatAppliesBrowserTargetsWhenRequested(): void
+    {
+        $this->css->setBrowserTargets(['safari' => 8]);
+        $input = '.test { user-select: none; }';
+
+        $this->assertStringNotContainsString('-webkit-user-select', $this->css->format($input));
+        $this->assertSame(
+            $this->css->transform($input),
+            $this->css->format($input, ['targets' => true])
+        );
+    }
+
+    public function testFormatRejectsInvalidIndent(): void
+    {
+        $this->expectException(\Exception::class);
+
+        $this->css->format('body { color: red; }', ['indent' => -1]);
+    }
+
     public function testTransformWithoutTargets(): void
     {
         $input = 'body { color: red; }';
@@ -251,6 +326,192 @@ class LightningCSSTest extends TestCase
         $this->assertStringContainsString('calc', $result);
     }
 
+    public function testMinifyKeepsLeadingLicenseComment(): void
+    {
+        $result = $this->css->minify('/*! Bootstrap v5 | MIT */ body { color: red; }');
+
+        $this->assertStringStartsWith('/*! Bootstrap v5 | MIT */', $result);
+        $this->assertStringContainsString('body{color:red}', $result);
+    }
+
+    public function testMinifyCanDropLicenseComments(): void
+    {
+        $result = $this->css->minify('/*! Bootstrap v5 | MIT */ body { color: red; }', [
+            'license_comments' => false,
+        ]);
+
+        $this->assertSame('body{color:red}', $result);
+    }
+
+    public function testMinifyHoistsLicenseComments(): void
+    {
+        $input = '
+            /*! Bootstrap v5 | MIT */
+            body { color: red; }
+            /* regular comment */
+            /*! Icon font | OFL */
+            .icon { content: "/*! not a comment */"; }
+        ';
+        $result = $this->css->minify($input, ['hoist_license_comments' => true]);
+
+        $this->assertStringStartsWith("/*! Bootstrap v5 | MIT */\n/*! Icon font | OFL */\n", $result);
+        $this->assertStringNotContainsString('regular comment', $result);
+        $this->assertSame(1, substr_count($result, 'not a comment'));
+    }
+
+    public function testMinifyManyPreservesKeys(): void
+    {
+        $result = $this->css->minifyMany([
+            'storefront' => 'body { color: red; }',
+            'b2b' => '.container { margin: 0 auto; }',
+        ]);
+
+        $this->assertSame(['storefront', 'b2b'], array_keys($result));
+        $this->assertSame(['code' => 'body{color:red}', 'error' => null], $result['storefront']);
+        $this->assertSame(['code' => '.container{margin:0 auto}', 'error' => null], $result['b2b']);
+    }
+
+    public function testMinifyManyReportsErrorsPerFile(): void
+    {
+        $result = $this->css->minifyMany([
+            'body { color: red; }',
+            'not valid css {{{{',
+        ]);
+
+        $this->assertSame('body{color:red}', $result[0]['code']);
+        $this->assertNull($result[0]['error']);
+        $this->assertNull($result[1]['code']);
+        $this->assertStringContainsString('CSS parse error', $result[1]['error']);
+    }
+
+    public function testMinifyManyMatchesSequentialMinify(): void
+    {
+        $this->css->setBrowserTargets(['safari' => 8]);
+        $files = [];
+        for ($i = 0; $i < 32; $i++) {
+            $files["channel-$i"] = ".c$i { user-select: none; color: #ff0000; }";
+        }
+
+        $result = $this->css->minifyMany($files);
+
+        foreach ($files as $name => $source) {
+            $this->assertSame($this->css->minify($source), $result[$name]['code']);
+        }
+    }
+
+    public function testCacheIsDisabledByDefault(): void
+    {
+        LightningCSS::clearCache();
+        $this->css->minify('body { color: red; }');
+        $this->css->minify('body { color: red; }');
+
+        $stats = LightningCSS::getCacheStats();
+        $this->assertSame(0, $stats['hits']);
+        $this->assertSame(0, $stats['entries']);
+    }
+
+    public function testCacheHitsForIdenticalInput(): void
+    {
+        LightningCSS::clearCache();
+        $this->css->enableCache();
+
+        $first = $this->css->minify('body { color: #ff0000; }');
+        $second = $this->css->minify('body { color: #ff0000; }');
+
+        $stats = LightningCSS::getCacheStats();
+        $this->assertSame($first, $second);
+        $this->assertSame(1, $stats['hits']);
+        $this->assertSame(1, $stats['misses']);
+        $this->assertSame(1, $stats['entries']);
+    }
+
+    public function testCacheIsSharedBetweenInstances(): void
+    {
+        LightningCSS::clearCache();
+        $this->css->enableCache();
+        $this->css->minify('body { color: red; }');
+
+        $other = new LightningCSS();
+        $other->enableCache();
+        $other->minify('body { color: red; }');
+
+        $this->assertSame(1, LightningCSS::getCacheStats()['hits']);
+    }
+
+    public function testCacheKeyIncludesTargetsAndOptions(): void
+    {
+        LightningCSS::clearCache();
+        $this->css->enableCache();
+        $input = '/*! License */ .test { user-select: none; }';
+
+        $plain = $this->css->minify($input);
+        $withoutComments = $this->css->minify($input, ['license_comments' => false]);
+        $this->css->setBrowserTargets(['safari' => 8]);
+        $prefixed = $this->css->minify($input);
+
+        $this->assertStringContainsString('License', $plain);
+        $this->assertStringNotContainsString('License', $withoutComments);
+        $this->assertStringContainsString('-webkit-user-select', $prefixed);
+        $this->assertSame(0, LightningCSS::getCacheStats()['hits']);
+    }
+
+    public function testCacheEvictsLeastRecentlyUsed(): void
+    {
+        LightningCSS::clearCache();
+        LightningCSS::setCacheCapacity(2);
+        $this->css->enableCache();
+
+        $this->css->minify('.a { color: red; }');
+        $this->css->minify('.b { color: red; }');
+        $this->css->minify('.a { color: red; }');
+        $this->css->minify('.c { color: red; }');
+        $this->css->minify('.a { color: red; }');
+
+        $stats = LightningCSS::getCacheStats();
+        $this->assertSame(2, $stats['entries']);
+        $this->assertSame(2, $stats['capacity']);
+        $this->assertSame(1, $stats['evictions']);
+        $this->assertSame(2, $stats['hits']);
+    }
+
+    public function testClearCacheResetsStats(): void
+    {
+        $this->css->enableCache();
+        $this->css->minify('body { color: red; }');
+        $this->css->minify('body { color: red; }');
+
+        LightningCSS::clearCache();
+
+        $stats = LightningCSS::getCacheStats();
+        $this->assertSame(0, $stats['hits']);
+        $this->assertSame(0, $stats['misses']);
+        $this->assertSame(0, $stats['entries']);
+    }
+
+    public function testSetCacheCapacityRejectsInvalidCapacity(): void
+    {
+        $this->expectException(\Exception::class);
+
+        LightningCSS::setCacheCapacity(0);
+    }
+
+    public function testShrinkingCapacityEvictsEntries(): void
+    {
+        LightningCSS::clearCache();
+        $this->css->enableCache();
+        $this->css->minify('.a { color: red; }');
+        $this->css->minify('.b { color: red; }');
+        $this->css->minify('.c { color: red; }');
+
+        LightningCSS::setCacheCapacity(1);
+
+        $stats = LightningCSS::getCacheStats();
+        $this->assertSame(1, $stats['entries']);
+        $this->assertSame(2, $stats['evictions']);
+        $this->assertSame('.c{color:red}', $this->css->minify('.c { color: red; }'));
+        $this->assertSame(1, LightningCSS::getCacheStats()['hits']);
+    }
+
     public function testMinifyInvalidCSSThrows(): void
     {
         $this->expectException(\Exception::class);
@@ -0,0 +1,125 @@
+<?php
+
+declare(strict_types=1);
+
+namespace Shopware\PHPExtension\Tests;
+
+use PHPUnit\Framework\TestCase;
+use Shopware\PHPExtension\QuickJS\QuickClass;
+use Shopware\PHPExtension\QuickJS\QuickJS;
+
+class QuickClassTest extends TestCase
+{
+    private QuickJS $js;
+
+    protected function setUp(): void
+    {
+        $this->js = new QuickJS();
+
+        $money = new QuickClass('Money', fn (int $amount, string $currency) => new Money($amount, $currency), Money::class);
+        $money->registerMethod('format');
+        $money->registerMethod('add', fn (Money $self, Money $other) => $self->amount + $other->amount);
+        $money->registerMethod('times', fn (Money $self, int $factor) => new Money($self->amount * $factor, $self->currency));
+        $money->registerProperty('amount');
+        $money->registerStaticMethod('currencies', fn () => ['EUR', 'USD']);
+        $money->registerStaticMethod('zero', fn (string $currency) => new Money(0, $currency));
+        $money->registerStaticProperty('precision', 2);
+
+        $this->js->registerClass($money);
+    }
+
+    public function testNewCreatesInstance(): void
+    {
+        $this->assertTrue($this->js->eval('new Money(1999, "EUR") instanceof Money'));
+        $this->assertSame('function', $this->js->eval('typeof Money'));
+    }
+
+    public function testInstanceMethodCallsPhpObject(): void
+    {
+        $this->assertSame('19.99 EUR', $this->js->eval('new Money(1999, "EUR").format()'));
+    }
+
+    public function testMethodCallableReceivesObjects(): void
+    {
+        $this->assertSame(2500, $this->js->eval('new Money(1999, "EUR").add(new Money(501, "EUR"))'));
+    }
+
+    public function testPropertyReadsAndWritesPhpObject(): void
+    {
+        $this->assertSame('5.00 EUR', $this->js->eval('const price = new Money(1999, "EUR"); price.amount = 500; price.format()'));
+        $this->assertSame(500, $this->js->eval('price.amount'));
+    }
+
+    public function testStaticMembers(): void
+    {
+        $this->assertSame(['EUR', 'USD'], $this->js->eval('Money.currencies()'));
+        $this->assertSame(2, $this->js->eval('Money.precision'));
+    }
+
+    public function testInstanceIsReturnedAsPhpObject(): void
+    {
+        $money = $this->js->eval('new Money(100, "USD")');
+
+        $this->assertInstanceOf(Money::class, $money);
+        $this->assertSame('USD', $money->currency);
+    }
+
+    public function testMethodReturningPhpObjectReturnsInstance(): void
+    {
+        $this->assertTrue($this->js->eval('new Money(150, "EUR").times(2) instanceof Money'));
+        $this->assertSame('3.00 EUR', $this->js->eval('new Money(150, "EUR").times(2).format()'));
+        $this->assertSame('0.00 USD', $this->js->eval('Money.zero("USD").format()'));
+    }
+
+    public function testPhpObjectPassedToJavaScriptBecomesInstance(): void
+    {
+        $total = new Money(4999, 'EUR');
+        $this->js->setGlobal('total', $total);
+
+        $this->assertSame('49.99 EUR', $this->js->eval('total.format()'));
+        $this->js->eval('total.amount = 100');
+        $this->assertSame(100, $total->amount);
+        $this->assertSame($total, $this->js->eval('total'));
+    }
+
+    public function testObjectsOfUnregisteredClassesAreNotWrapped(): void
+    {
+        $js = new QuickJS();
+        $js->registerClass(new QuickClass('Money', fn (int $amount, string $currency) => new Money($amount, $currency)));
+        $js->setGlobal('total', new Money(4999, 'EUR'));
+
+        $this->assertFalse($js->eval('total instanceof Money'));
+    }
+
+    public function testSubclassInheritsMethods(): void
+    {
+        $this->js->eval('class Price extends Money { label() { return "Price: " + this.format(); } }');
+
+        $this->assertSame('Price: 1.00 EUR', $this->js->eval('new Price(100, "EUR").label()'));
+        $this->assertTrue($this->js->eval('new Price(100, "EUR") instanceof Money'));
+    }
+
+    public function testCallWithoutNewThrows(): void
+    {
+        $this->expectException(\Exception::class);
+        $this->js->eval('Money(1999, "EUR")');
+    }
+
+    public function testMethodOnOtherReceiverThrows(): void
+    {
+        $this->expectException(\Exception::class);
+        $this->js->eval('Money.prototype.format.call({})');
+    }
+}
+
+class Money
+{
+    public function __construct(public int $amount, public string $currency)
+    {
+    }
+
+    public function format(): string
+    {
+        return sprintf('%.2f %s', $this->amount / 100, $this->currency);
+    }
+}
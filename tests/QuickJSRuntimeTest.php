@@ -0,0 +1,141 @@
+<?php
+
+declare(strict_types=1);
+
+namespace Shopware\PHPExtension\Tests;
+
+use PHPUnit\Framework\TestCase;
+use Shopware\PHPExtension\QuickJS\QuickJSContext;
+use Shopware\PHPExtension\QuickJS\QuickJSRuntime;
+use Shopware\PHPExtension\QuickJS\QuickObject;
+
+class QuickJSRuntimeTest extends TestCase
+{
+    private QuickJSRuntime $runtime;
+
+    protected function setUp(): void
+    {
+        $this->runtime = new QuickJSRuntime();
+    }
+
+    public function testCreateContext(): void
+    {
+        $context = $this->runtime->createContext();
+
+        $this->assertInstanceOf(QuickJSContext::class, $context);
+        $this->assertSame(3, $context->eval('1 + 2'));
+    }
+
+    public function testContextsHaveIsolatedGlobals(): void
+    {
+        $first = $this->runtime->createContext();
+        $second = $this->runtime->createContext();
+
+        $first->setGlobal('name', 'first');
+        $second->eval("var name = 'second'");
+
+        $this->assertSame('first', $first->getGlobal('name'));
+        $this->assertSame('second', $second->getGlobal('name'));
+
+        $first->eval('var onlyFirst = 1');
+        $this->assertFalse($second->hasGlobal('onlyFirst'));
+    }
+
+    public function testContextsCanUseDifferentIntrinsics(): void
+    {
+        $full = $this->runtime->createContext();
+        $restricted = $this->runtime->createContext(['intrinsics' => ['json']]);
+
+        $this->assertSame('function', $full->eval('typeof Proxy'));
+        $this->assertSame('undefined', $restricted->eval('typeof Proxy'));
+    }
+
+    public function testContextRegistersFunctionsAndObjects(): void
+    {
+        $context = $this->runtime->createContext();
+        $context->registerFunction('upper', 'strtoupper');
+
+        $obj = new QuickObject();
+        $obj->registerProperty('version', '1.0.0');
+        $context->registerObject('App', $obj);
+
+        $this->assertSame('HELLO', $context->eval("upper('hello')"));
+        $this->assertSame('1.0.0', $context->eval('App.version'));
+
+        $context->eval('function add(a, b) { return a + b; }');
+        $this->assertSame(5, $context->call('add', 2, 3));
+    }
+
+    public function testMemoryLimitIsSharedByContexts(): void
+    {
+        $runtime = new QuickJSRuntime(['memory_limit' => 2 * 1024 * 1024]);
+        $context = $runtime->createContext();
+
+        $this->expectException(\Exception::class);
+        $context->eval("let parts = []; while (true) { parts.push('x'.repeat(1024)); }");
+    }
+
+    public function testMemoryUsageCoversAllContexts(): void
+    {
+        $before = $this->runtime->memoryUsage();
+        $context = $this->runtime->createContext();
+        $context->eval("var data = 'x'.repeat(100000)");
+
+        $this->assertGreaterThan($before, $this->runtime->memoryUsage());
+    }
+
+    public function testMemoryStatsCoverAllContexts(): void
+    {
+        $runtime = new QuickJSRuntime(['gc_threshold' => 1024 * 1024]);
+        $before = $runtime->getMemoryStats()['str_count'];
+        $context = $runtime->createContext();
+        $context->eval("var names = ['first', 'second'].map((name) => name + Math.random())");
+
+        $this->assertGreaterThan($before, $runtime->getMemoryStats()['str_count']);
+    }
+
+    public function testDisposedContextThrows(): void
+    {
+        $context = $this->runtime->createContext();
+        $this->assertFalse($context->isDisposed());
+
+        $context->dispose();
+        $this->assertTrue($context->isDisposed());
+
+        $this->expectException(\Exception::class);
+        $this->expectExceptionMessage('Context has been disposed');
+        $context->eval('1');
+    }
+
+    public function testDisposeWhileRunningThrows(): void
+    {
+        $context = $this->runtime->createContext();
+        $error = null;
+
+        $app = new QuickObject();
+        $app->registerGetter('close', function () use ($context, &$error) {
+            try {
+                $context->dispose();
+            } catch (\Exception $e) {
+                $error = $e->getMessage();
+            }
+
+            return true;
+        });
+        $context->registerObject('App', $app);
+
+        $this->assertTrue($context->eval('App.close'));
+        $this->assertSame('Cannot dispose while JavaScript is running', $error);
+        $this->assertFalse($context->isDisposed());
+    }
+
+    public function testDisposingOneContextKeepsOthers(): void
+    {
+        $first = $this->runtime->createContext();
+        $second = $this->runtime->createContext();
+
+        $first->dispose();
+
+        $this->assertSame(2, $second->eval('1 + 1'));
+    }
+}
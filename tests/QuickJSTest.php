@@ -5,6 +5,7 @@ declare(strict_types=1);
 namespace Shopware\PHPExtension\Tests;
 
 use PHPUnit\Framework\TestCase;
+use Shopware\PHPExtension\QuickJS\QuickBinary;
 use Shopware\PHPExtension\QuickJS\QuickJS;
 use Shopware\PHPExtension\QuickJS\QuickObject;
 
@@ -114,31 +115,346 @@ class QuickJSTest extends TestCase
         $this->assertSame('boolean', $this->js->typeofGlobal('flag'));
     }
 
+    public function testTypeofDottedPath(): void
+    {
+        $this->js->eval('var lib = { utils: { format: function (value) { return value; } } }');
+
+        $this->assertSame('function', $this->js->typeofGlobal('lib.utils.format'));
+        $this->assertSame('undefined', $this->js->typeofGlobal('lib.utils.missing'));
+    }
+
+    public function testTypeofDistinguishesBigIntSymbolAndPromise(): void
+    {
+        $this->js->eval('var big = 10n; var sym = Symbol("id"); var pending = Promise.resolve(1); var list = [1]; var nothing = null');
+
+        $this->assertSame('bigint', $this->js->typeofGlobal('big'));
+        $this->assertSame('symbol', $this->js->typeofGlobal('sym'));
+        $this->assertSame('promise', $this->js->typeofGlobal('pending'));
+        $this->assertSame('array', $this->js->typeofGlobal('list'));
+        $this->assertSame('null', $this->js->typeofGlobal('nothing'));
+    }
+
+    public function testListGlobals(): void
+    {
+        $this->js->setGlobal('config', ['debug' => true]);
+        $this->js->eval('var counter = 1; function greet() {}');
+
+        $globals = $this->js->listGlobals();
+
+        $this->assertSame(['config', 'counter', 'greet'], array_values(array_intersect($globals, ['config', 'counter', 'greet'])));
+        $this->assertNotContains('JSON', $globals);
+    }
+
+    public function testDescribe(): void
+    {
+        $this->js->eval('class Base {} class Item extends Base { constructor() { super(); this.id = 1; } } var item = new Item(); function add(a, b) { return a + b; }');
+
+        $this->assertSame(
+            ['type' => 'object', 'keys' => ['id'], 'prototypes' => ['Item', 'Base', 'Object']],
+            $this->js->describe('item'),
+        );
+
+        $add = $this->js->describe('add');
+        $this->assertSame('function', $add['type']);
+        $this->assertSame('add', $add['name']);
+        $this->assertSame(2, $add['length']);
+        $this->assertSame(['Function', 'Object'], $add['prototypes']);
+    }
+
     public function testCallJavaScriptFunction(): void
     {
         $this->js->eval("function greet(name) { return 'Hello, ' + name + '!'; }");
-        $result = $this->js->call('greet', ['World']);
+        $result = $this->js->call('greet', 'World');
         $this->assertSame('Hello, World!', $result);
     }
 
     public function testCallJavaScriptFunctionWithMultipleArgs(): void
     {
         $this->js->eval("function add(a, b) { return a + b; }");
-        $result = $this->js->call('add', [10, 20]);
+        $result = $this->js->call('add', 10, 20);
         $this->assertSame(30, $result);
     }
 
     public function testCallJavaScriptFunctionWithArrayArg(): void
     {
         $this->js->eval("function sum(arr) { return arr.reduce((a, b) => a + b, 0); }");
-        $result = $this->js->call('sum', [[1, 2, 3, 4, 5]]);
+        $result = $this->js->call('sum', [1, 2, 3, 4, 5]);
         $this->assertSame(15, $result);
     }
 
     public function testCallNonexistentFunctionThrows(): void
     {
         $this->expectException(\Exception::class);
-        $this->js->call('nonexistent', []);
+        $this->js->call('nonexistent');
+    }
+
+    public function testCallByDottedPath(): void
+    {
+        $this->js->eval('var MyLib = { utils: { prefix: "#", format(n) { return this.prefix + n; } } }');
+
+        $this->assertSame('#42', $this->js->call('MyLib.utils.format', 42));
+    }
+
+    public function testCallWithExplicitThis(): void
+    {
+        $this->js->eval('
+            var MyLib = { utils: { format(n) { return this.prefix + n; } } };
+            var euro = { prefix: "€" };
+        ');
+
+        $this->assertSame('€42', $this->js->apply('MyLib.utils.format', [42], 'euro'));
+    }
+
+    public function testCallThroughMissingPathThrows(): void
+    {
+        $this->expectException(\Exception::class);
+        $this->expectExceptionMessage("'MyLib' is not an object");
+        $this->js->call('MyLib.utils.format');
+    }
+
+    public function testCallPassesAssociativeArrayAsObject(): void
+    {
+        $this->js->eval('function describe(name, options) { return name + ":" + options.currency + ":" + arguments.length; }');
+
+        $this->assertSame('price:EUR:2', $this->js->call('describe', 'price', ['currency' => 'EUR']));
+    }
+
+    public function testApplyRejectsAssociativeArguments(): void
+    {
+        $this->js->eval('function describe(options) { return options; }');
+
+        $this->expectException(\Exception::class);
+        $this->expectExceptionMessage('Arguments must be a list');
+        $this->js->apply('describe', ['currency' => 'EUR']);
+    }
+
+    public function testCallMethodOnClassInstance(): void
+    {
+        $this->js->eval('
+            class Cart {
+                constructor() { this.items = []; }
+                addItem(id, quantity) { this.items.push({ id, quantity }); return this.items.length; }
+            }
+            var shop = { cart: new Cart() };
+        ');
+
+        $this->assertSame(1, $this->js->callMethod('shop.cart', 'addItem', ['SW-1000', 2]));
+        $this->assertSame([['id' => 'SW-1000', 'quantity' => 2]], $this->js->eval('shop.cart.items'));
+    }
+
+    public function testCallMethodWithMissingMethodThrows(): void
+    {
+        $this->js->eval('var shop = {}');
+
+        $this->expectException(\Exception::class);
+        $this->expectExceptionMessage('Method not found: shop.checkout');
+        $this->js->callMethod('shop', 'checkout', []);
+    }
+
+    public function testDateIsReturnedAsDateTimeImmutable(): void
+    {
+        $date = $this->js->eval('new Date(Date.UTC(2024, 0, 15, 10, 30, 0, 250))');
+
+        $this->assertInstanceOf(\DateTimeImmutable::class, $date);
+        $this->assertSame('2024-01-15T10:30:00.250+00:00', $date->format('Y-m-d\TH:i:s.vP'));
+    }
+
+    public function testInvalidDateIsReturnedAsNull(): void
+    {
+        $this->assertNull($this->js->eval('new Date(NaN)'));
+    }
+
+    public function testDateTimeIsPassedAsDate(): void
+    {
+        $this->js->setGlobal('created', new \DateTime('2024-01-15 10:30:00.250', new \DateTimeZone('Europe/Berlin')));
+
+        $this->assertTrue($this->js->eval('created instanceof Date'));
+        $this->assertSame('2024-01-15T09:30:00.250Z', $this->js->eval('created.toISOString()'));
+    }
+
+    public function testPreEpochDateTimeKeepsMilliseconds(): void
+    {
+        $this->js->setGlobal('before', new \DateTimeImmutable('1969-12-31 23:59:58.500', new \DateTimeZone('UTC')));
+
+        $this->assertSame('-1500', $this->js->eval('String(before.getTime())'));
+        $this->assertSame('1969-12-31T23:59:58.500Z', $this->js->eval('before.toISOString()'));
+        $this->assertSame('1969-12-31 23:59:58.500', $this->js->eval('before')->format('Y-m-d H:i:s.v'));
+    }
+
+    public function testMapIsReturnedAsAssociativeArray(): void
+    {
+        $result = $this->js->eval('new Map([["name", "Shirt"], [42, true]])');
+
+        $this->assertSame(['name' => 'Shirt', 42 => true], $result);
+    }
+
+    public function testSetIsReturnedAsList(): void
+    {
+        $this->assertSame(['a', 'b'], $this->js->eval('new Set(["a", "b", "a"])'));
+    }
+
+    public function testRegExpIsReturnedAsSource(): void
+    {
+        $this->assertSame('/ab+c/gi', $this->js->eval('/ab+c/gi'));
+    }
+
+    public function testBinaryDataIsReturnedAsString(): void
+    {
+        $this->assertSame("\x00\xffA", $this->js->eval('new Uint8Array([0, 255, 65])'));
+        $this->assertSame("\x01\x02", $this->js->eval('new Uint8Array([1, 2]).buffer'));
+        $this->assertSame([1.5, 2.0], $this->js->eval('new Float64Array([1.5, 2])'));
+    }
+
+    public function testQuickBinaryIsPassedAsUint8Array(): void
+    {
+        $binary = new QuickBinary("\x00\xffA");
+        $this->assertSame(3, $binary->length());
+        $this->assertSame("\x00\xffA", $binary->getData());
+
+        $this->js->setGlobal('data', $binary);
+
+        $this->assertTrue($this->js->eval('data instanceof Uint8Array'));
+        $this->assertSame([0, 255, 65], $this->js->eval('Array.from(data)'));
+    }
+
+    public function testSetGlobalJson(): void
+    {
+        $this->js->setGlobalJson('products', '[{"name": "Shirt", "price": 19.99}, {"name": "Cap", "price": 9.5}]');
+
+        $this->assertSame(29.49, $this->js->eval('products.reduce((sum, p) => sum + p.price, 0)'));
+    }
+
+    public function testSetGlobalJsonWithInvalidJsonThrows(): void
+    {
+        $this->expectException(\Exception::class);
+        $this->expectExceptionMessage('Invalid JSON');
+        $this->js->setGlobalJson('products', '{"name": ');
+    }
+
+    public function testEvalJson(): void
+    {
+        $this->assertSame('{"total":3,"items":["a","b"]}', $this->js->evalJson('({ total: 1 + 2, items: ["a", "b"] })'));
+        $this->assertSame('null', $this->js->evalJson('undefined'));
+    }
+
+    public function testNumericStringKeysBecomeIntegerKeys(): void
+    {
+        $result = $this->js->eval('({ "10": "a", "-1": "b", "01": "c", name: "d" })');
+
+        $this->assertSame([10 => 'a', '-1' => 'b', '01' => 'c', 'name' => 'd'], $result);
+        $this->assertSame('a', $result[10]);
+    }
+
+    public function testAssociativeArrayRoundTrip(): void
+    {
+        $data = [3 => 'three', 7 => 'seven', 'name' => 'product'];
+        $this->js->setGlobal('data', $data);
+
+        $this->assertSame($data, $this->js->eval('data'));
+    }
+
+    public function testSparseArrayKeepsIndices(): void
+    {
+        $this->assertSame([0 => 1, 2 => 3], $this->js->eval('[1, , 3]'));
+    }
+
+    public function testEmptyArrayMode(): void
+    {
+        $this->js->setGlobal('items', []);
+        $this->assertTrue($this->js->eval('Array.isArray(items)'));
+
+        $js = new QuickJS(['empty_array' => 'object']);
+        $js->setGlobal('items', []);
+        $this->assertSame('[object Object]', $js->eval('Object.prototype.toString.call(items)'));
+    }
+
+    public function testStdClassIsPassedAsObject(): void
+    {
+        $this->js->setGlobal('config', (object) ['debug' => true, 'empty' => new \stdClass()]);
+
+        $this->assertTrue($this->js->eval('config.debug'));
+        $this->assertSame('{}', $this->js->eval('JSON.stringify(config.empty)'));
+    }
+
+    public function testObjectsModeReturnsStdClass(): void
+    {
+        $js = new QuickJS(['objects' => 'stdclass']);
+
+        $result = $js->eval('({ name: "Shirt", tags: ["a"], size: { eu: 42 } })');
+        $this->assertInstanceOf(\stdClass::class, $result);
+        $this->assertSame(['a'], $result->tags);
+        $this->assertSame(42, $result->size->eu);
+    }
+
+    public function testObjectsModeRoundTripsEmptyObject(): void
+    {
+        $js = new QuickJS(['objects' => 'stdclass']);
+
+        $js->setGlobal('copy', $js->eval('({})'));
+        $this->assertSame('[object Object]', $js->eval('Object.prototype.toString.call(copy)'));
+        $this->assertSame('{}', $js->eval('JSON.stringify(copy)'));
+    }
+
+    public function testObjectsModeRoundTripsNumericStringKeys(): void
+    {
+        $js = new QuickJS(['objects' => 'stdclass']);
+
+        $js->setGlobal('copy', $js->eval('({"0": "a"})'));
+        $this->assertFalse($js->eval('Array.isArray(copy)'));
+        $this->assertSame('{"0":"a"}', $js->eval('JSON.stringify(copy)'));
+    }
+
+    public function testUnknownObjectsModeThrows(): void
+    {
+        $this->expectExceptionMessage("Unknown objects mode 'json'");
+        new QuickJS(['objects' => 'json']);
+    }
+
+    public function testLargeIntegerIsPreserved(): void
+    {
+        $this->js->setGlobal('id', 4294967296);
+
+        $this->assertSame('4294967297', $this->js->eval('String(id + 1)'));
+    }
+
+    public function testCircularObjectThrows(): void
+    {
+        $this->expectException(\Exception::class);
+        $this->expectExceptionMessage('Circular reference');
+        $this->js->eval('var a = {}; a.self = a; a');
+    }
+
+    public function testCircularObjectBecomesMarker(): void
+    {
+        $js = new QuickJS(['circular' => 'marker']);
+
+        $this->assertSame(['self' => '[Circular]'], $js->eval('var a = {}; a.self = a; a'));
+        // Values referenced twice without a cycle are converted twice
+        $this->assertSame([[1], [1]], $js->eval('var b = [1]; [b, b]'));
+    }
+
+    public function testMaxDepthIsEnforced(): void
+    {
+        $js = new QuickJS(['max_depth' => 3]);
+        $this->assertSame([[[1]]], $js->eval('[[[1]]]'));
+
+        $this->expectException(\Exception::class);
+        $this->expectExceptionMessage('Maximum nesting depth of 3 exceeded');
+        $js->eval('[[[[1]]]]');
+    }
+
+    public function testPhpReferencesAreConverted(): void
+    {
+        $value = 'shared';
+        $this->js->setGlobal('refs', ['a' => &$value, 'b' => &$value]);
+        $this->assertSame('shared', $this->js->eval('refs.b'));
+
+        $loop = ['name' => 'loop'];
+        $loop['self'] = &$loop;
+
+        $this->expectException(\Exception::class);
+        $this->expectExceptionMessage('Circular reference');
+        $this->js->setGlobal('loop', $loop);
     }
 
     public function testContextMaintainsState(): void
@@ -164,6 +480,407 @@ class QuickJSTest extends TestCase
         $this->assertSame('instance2', $js2->eval('value'));
     }
 
+    public function testRestrictedIntrinsics(): void
+    {
+        $js = new QuickJS(['intrinsics' => ['json']]);
+
+        $this->assertSame('{"a":1}', $js->eval('JSON.stringify({a: 1})'));
+        $this->assertSame('undefined', $js->eval('typeof Proxy'));
+        $this->assertSame('undefined', $js->eval('typeof RegExp'));
+        $this->assertSame('undefined', $js->eval('typeof Date'));
+    }
+
+    public function testEvalCanBeDisabledForScripts(): void
+    {
+        $js = new QuickJS(['intrinsics' => ['json', 'date']]);
+
+        $this->assertSame(3, $js->eval('1 + 2'));
+        $this->assertSame('undefined', $js->eval('typeof eval'));
+        $this->assertSame('undefined', $js->eval('typeof Function'));
+        $this->assertSame('blocked', $js->eval("
+            try { (function () {}).constructor('return 1')(); 'allowed' } catch (e) { 'blocked' }
+        "));
+    }
+
+    public function testEvalAvailableWhenRequested(): void
+    {
+        $js = new QuickJS(['intrinsics' => ['eval']]);
+
+        $this->assertSame(2, $js->eval("eval('1 + 1')"));
+        $this->assertSame(3, $js->eval("new Function('return 3')()"));
+    }
+
+    public function testUnknownIntrinsicThrows(): void
+    {
+        $this->expectException(\Exception::class);
+
+        new QuickJS(['intrinsics' => ['filesystem']]);
+    }
+
+    public function testFreezeGlobals(): void
+    {
+        $this->js->registerFunction('upper', 'strtoupper');
+        $this->js->freezeGlobals();
+
+        $this->assertSame('HELLO', $this->js->eval("upper('hello')"));
+        $this->assertSame('function', $this->js->eval("upper = null; typeof upper"));
+        $this->assertSame('undefined', $this->js->eval("globalThis.injected = 1; typeof injected"));
+    }
+
+    public function testSetGlobalAfterFreezeThrows(): void
+    {
+        $this->js->freezeGlobals();
+
+        $this->expectException(\Exception::class);
+        $this->js->setGlobal('late', 1);
+    }
+
+    public function testRestoreReturnsToSnapshot(): void
+    {
+        $js = new QuickJS(['snapshots' => true]);
+        $js->eval('var library = { greet: (name) => "Hello " + name }');
+        $js->registerFunction('upper', 'strtoupper');
+        $js->snapshot();
+
+        $js->eval('var leaked = true; Array.prototype.polluted = 1; library.greet = () => "changed"');
+        $js->setGlobal('requestId', 42);
+        $js->restore();
+
+        $this->assertFalse($js->hasGlobal('leaked'));
+        $this->assertFalse($js->hasGlobal('requestId'));
+        $this->assertSame('undefined', $js->eval('typeof [].polluted'));
+        $this->assertSame('Hello World', $js->eval('library.greet("World")'));
+        $this->assertSame('HELLO', $js->eval("upper('hello')"));
+    }
+
+    public function testRestoreCanBeRepeated(): void
+    {
+        $js = new QuickJS(['snapshots' => true]);
+        $js->eval('var counter = 0');
+        $js->snapshot();
+
+        for ($i = 0; $i < 3; $i++) {
+            $this->assertSame(1, $js->eval('++counter'));
+            $js->restore();
+        }
+    }
+
+    public function testSnapshotRequiresOption(): void
+    {
+        $this->expectException(\Exception::class);
+        $this->js->snapshot();
+    }
+
+    public function testRestoreWithoutSnapshotThrows(): void
+    {
+        $js = new QuickJS(['snapshots' => true]);
+
+        $this->expectException(\Exception::class);
+        $js->restore();
+    }
+
+    public function testFailedRestoreKeepsContextTimersAndSnapshot(): void
+    {
+        $calls = 0;
+        $loader = new QuickObject();
+        $loader->registerGetter('config', function () use (&$calls) {
+            if (++$calls > 1) {
+                throw new \RuntimeException('Config unavailable');
+            }
+
+            return 'loaded';
+        });
+
+        $js = new QuickJS(['snapshots' => true, 'timers' => true]);
+        $js->registerObject('Loader', $loader);
+        $js->eval('var config = Loader.config');
+        $js->snapshot();
+        $js->eval('var fired = false; setTimeout(() => { fired = true }, 10)');
+
+        try {
+            $js->restore();
+            $this->fail('restore() should fail while the getter throws');
+        } catch (\Exception) {
+        }
+
+        $this->assertFalse($js->runEventLoop());
+        $this->assertTrue($js->eval('fired'));
+
+        $calls = 0;
+        $js->restore();
+        $this->assertSame('loaded', $js->eval('config'));
+        $this->assertFalse($js->hasGlobal('fired'));
+    }
+
+    public function testRestoreFromGetterDuringGetGlobalThrows(): void
+    {
+        $js = new QuickJS(['snapshots' => true]);
+        $js->snapshot();
+        $error = null;
+
+        $app = new QuickObject();
+        $app->registerGetter('state', function () use ($js, &$error) {
+            try {
+                $js->restore();
+            } catch (\Exception $e) {
+                $error = $e->getMessage();
+            }
+
+            return 'running';
+        });
+        $js->registerObject('App', $app);
+
+        $this->assertSame(['state' => 'running'], $js->getGlobal('App'));
+        $this->assertSame('Cannot restore while JavaScript is running', $error);
+    }
+
+    public function testCallBeforeSnapshotThrows(): void
+    {
+        $js = new QuickJS(['snapshots' => true, 'timers' => true]);
+        $js->eval('function init() { return 1 }');
+
+        foreach ([
+            fn () => $js->call('init'),
+            fn () => $js->apply('init', []),
+            fn () => $js->callMethod('globalThis', 'init', []),
+            fn () => $js->runEventLoop(),
+        ] as $before) {
+            try {
+                $before();
+                $this->fail('Calls before snapshot() should throw');
+            } catch (\Exception $e) {
+                $this->assertStringContainsString('cannot be replayed by restore()', $e->getMessage());
+            }
+        }
+
+        $js->snapshot();
+        $this->assertSame(1, $js->call('init'));
+        $this->assertFalse($js->runEventLoop());
+    }
+
+    public function testRestoreDoesNotRepeatConsoleOutput(): void
+    {
+        $js = new QuickJS(['snapshots' => true, 'console_capture' => true]);
+        $js->eval('console.log("loaded"); var ready = true');
+        $js->snapshot();
+        $js->restore();
+
+        $this->assertTrue($js->eval('ready'));
+        $this->assertSame("[info] loaded\n", $js->getConsoleOutput());
+    }
+
+    public function testRestoreReplaysValuesAsTheyWereSet(): void
+    {
+        $config = new \stdClass();
+        $config->theme = 'light';
+        $items = [$config];
+
+        $js = new QuickJS(['snapshots' => true]);
+        $js->setGlobal('config', $config);
+        $js->addObjectProperty('App', 'items', $items);
+        $js->snapshot();
+
+        $config->theme = 'dark';
+        $js->restore();
+
+        $this->assertSame('light', $js->eval('config.theme'));
+        $this->assertSame('light', $js->eval('App.items[0].theme'));
+    }
+
+    public function testRestoreReplaysFailedSetupScripts(): void
+    {
+        $js = new QuickJS(['snapshots' => true]);
+        try {
+            $js->eval('var partial = 1; throw new Error("broken")');
+        } catch (\Exception) {
+        }
+        $js->snapshot();
+        $js->restore();
+
+        $this->assertSame(1, $js->eval('partial'));
+    }
+
+    public function testChangesAfterSnapshotAreNotRecorded(): void
+    {
+        $js = new QuickJS(['snapshots' => true]);
+        $js->eval('var counter = 0');
+        $js->snapshot();
+
+        $js->eval('counter++');
+        $js->registerFunction('upper', 'strtoupper');
+        $js->restore();
+
+        $this->assertSame(0, $js->eval('counter'));
+        $this->assertFalse($js->hasGlobal('upper'));
+    }
+
+    public function testSnapshotCanOnlyBeTakenOnce(): void
+    {
+        $js = new QuickJS(['snapshots' => true]);
+        $js->snapshot();
+
+        $this->expectExceptionMessage('A snapshot has already been taken');
+        $js->snapshot();
+    }
+
+    public function testConsoleExistsByDefault(): void
+    {
+        $this->assertSame('object', $this->js->eval('typeof console'));
+        $this->js->eval('console.log("discarded")');
+    }
+
+    public function testConsoleOutputIsPassedToCallable(): void
+    {
+        $messages = [];
+        $js = new QuickJS(['console' => function (string $level, string $message) use (&$messages): void {
+            $messages[] = [$level, $message];
+        }]);
+
+        $js->eval('console.log("Hello", 42, { a: [1, "x"] }); console.warn("careful"); console.error("broken")');
+
+        $this->assertSame([
+            ['info', "Hello 42 { a: [ 1, 'x' ] }"],
+            ['warning', 'careful'],
+            ['error', 'broken'],
+        ], $messages);
+    }
+
+    public function testConsoleOutputIsPassedToLogger(): void
+    {
+        $logger = new class {
+            public array $records = [];
+
+            public function log($level, $message, array $context = []): void
+            {
+                $this->records[] = [$level, $message];
+            }
+        };
+
+        $js = new QuickJS(['console' => $logger]);
+        $js->eval('console.debug("details"); console.info("started")');
+
+        $this->assertSame([['debug', 'details'], ['info', 'started']], $logger->records);
+    }
+
+    public function testConsoleCapture(): void
+    {
+        $js = new QuickJS(['console_capture' => true]);
+        $js->eval('console.log("first"); console.error("second")');
+
+        $this->assertSame("[info] first\n[error] second\n", $js->getConsoleOutput());
+
+        $js->clearConsoleOutput();
+        $this->assertSame('', $js->getConsoleOutput());
+    }
+
+    public function testConsoleTableAndTimers(): void
+    {
+        $js = new QuickJS(['console_capture' => true]);
+        $js->eval('console.table([{ name: "a", size: 1 }, { name: "b", size: 2 }])');
+        $js->eval('console.time("render"); console.timeEnd("render")');
+
+        $output = $js->getConsoleOutput();
+        $this->assertStringContainsString("(index) | name | size", $output);
+        $this->assertStringContainsString("1       | 'b'  | 2", $output);
+        $this->assertMatchesRegularExpression('/render: \d+\.\d{3}ms/', $output);
+    }
+
+    public function testGetConsoleOutputWithoutCaptureThrows(): void
+    {
+        $this->expectException(\Exception::class);
+        $this->js->getConsoleOutput();
+    }
+
+    public function testTimersAreDisabledByDefault(): void
+    {
+        $this->assertSame('undefined', $this->js->eval('typeof setTimeout'));
+    }
+
+    public function testTimersRunInOrderOfVirtualTime(): void
+    {
+        $js = new QuickJS(['timers' => true]);
+        $js->eval('
+            var order = [];
+            setTimeout(() => order.push("slow"), 5000);
+            setTimeout((label) => order.push(label), 10, "fast");
+            queueMicrotask(() => order.push("microtask"));
+            Promise.resolve().then(() => order.push("promise"));
+            order.push("sync");
+        ');
+
+        $this->assertFalse($js->runEventLoop());
+        $this->assertSame(['sync', 'microtask', 'promise', 'fast', 'slow'], $js->getGlobal('order'));
+    }
+
+    public function testRunEventLoopStopsAtDeadline(): void
+    {
+        $js = new QuickJS(['timers' => true]);
+        $js->eval('var fired = []; setTimeout(() => fired.push(100), 100); setTimeout(() => fired.push(300), 300)');
+
+        $this->assertTrue($js->runEventLoop(200));
+        $this->assertSame([100], $js->getGlobal('fired'));
+
+        $this->assertFalse($js->runEventLoop(200));
+        $this->assertSame([100, 300], $js->getGlobal('fired'));
+    }
+
+    public function testSetIntervalAndClearInterval(): void
+    {
+        $js = new QuickJS(['timers' => true]);
+        $js->eval('
+            var ticks = 0;
+            var id = setInterval(() => { if (++ticks === 3) clearInterval(id); }, 50);
+        ');
+
+        $this->assertFalse($js->runEventLoop());
+        $this->assertSame(3, $js->getGlobal('ticks'));
+    }
+
+    public function testUnclearedIntervalThrowsInsteadOfHanging(): void
+    {
+        $js = new QuickJS(['timers' => true]);
+        $js->eval('var ticks = 0; setInterval(() => { ticks++ }, 10)');
+
+        try {
+            $js->runEventLoop();
+            $this->fail('runEventLoop() should not run an endless interval forever');
+        } catch (\Exception $e) {
+            $this->assertStringContainsString('did not become idle', $e->getMessage());
+        }
+
+        $ticks = $js->getGlobal('ticks');
+        $this->assertTrue($js->runEventLoop(100));
+        $this->assertSame($ticks + 10, $js->getGlobal('ticks'));
+    }
+
+    public function testSelfReschedulingTimeoutThrowsInsteadOfHanging(): void
+    {
+        $js = new QuickJS(['timers' => true]);
+        $js->eval('(function again() { setTimeout(again, 0) })()');
+
+        $this->expectException(\Exception::class);
+        $this->expectExceptionMessage('did not become idle');
+        $js->runEventLoop(1000);
+    }
+
+    public function testClearTimeout(): void
+    {
+        $js = new QuickJS(['timers' => true]);
+        $js->eval('var fired = false; clearTimeout(setTimeout(() => { fired = true }, 10))');
+
+        $this->assertFalse($js->runEventLoop());
+        $this->assertFalse($js->getGlobal('fired'));
+    }
+
+    public function testTimerCallbackErrorThrows(): void
+    {
+        $js = new QuickJS(['timers' => true]);
+        $js->eval('setTimeout(() => { throw new Error("boom") }, 0)');
+
+        $this->expectException(\Exception::class);
+        $js->runEventLoop();
+    }
+
     public function testSetMemoryLimit(): void
     {
         $this->js->setMemoryLimit(10 * 1024 * 1024);
@@ -189,6 +906,26 @@ class QuickJSTest extends TestCase
         $this->assertGreaterThan(0, $usage);
     }
 
+    public function testGetMemoryStats(): void
+    {
+        $before = $this->js->getMemoryStats();
+        $this->js->eval('var items = []; for (let i = 0; i < 1000; i++) { items.push({ id: i }); }');
+        $after = $this->js->getMemoryStats();
+
+        foreach (['malloc_count', 'atom_count', 'str_count', 'obj_count', 'prop_count', 'shape_count', 'js_func_count', 'array_count', 'binary_object_count'] as $key) {
+            $this->assertIsInt($after[$key], $key);
+        }
+        $this->assertSame($this->js->memoryUsage(), $after['memory_used_size']);
+        $this->assertGreaterThan($before['obj_count'] + 999, $after['obj_count']);
+    }
+
+    public function testSetGcThreshold(): void
+    {
+        $this->js->setGcThreshold(256 * 1024);
+
+        $this->assertSame(3, $this->js->eval('[1, 2, 3].length'));
+    }
+
     public function testGcDoesNotThrow(): void
     {
         $this->js->eval("const data = [1, 2, 3]");
@@ -432,6 +1169,230 @@ class QuickJSTest extends TestCase
         $this->assertSame(3306, $this->js->eval("Config.database.port"));
     }
 
+    public function testQuickObjectGetterIsReadLazily(): void
+    {
+        $currency = 'EUR';
+        $obj = new QuickObject();
+        $obj->registerGetter('currency', function () use (&$currency) {
+            return $currency;
+        });
+        $this->js->registerObject('Shop', $obj);
+
+        $this->assertSame('EUR', $this->js->eval('Shop.currency'));
+        $currency = 'USD';
+        $this->assertSame('USD', $this->js->eval('Shop.currency'));
+    }
+
+    public function testQuickObjectSetterForwardsWrites(): void
+    {
+        $currency = 'EUR';
+        $obj = new QuickObject();
+        $obj->registerGetter(
+            'currency',
+            function () use (&$currency) {
+                return $currency;
+            },
+            function (string $value) use (&$currency): void {
+                $currency = strtoupper($value);
+            },
+        );
+        $this->js->registerObject('Shop', $obj);
+
+        $this->assertSame('GBP', $this->js->eval('Shop.currency = "gbp"; Shop.currency'));
+        $this->assertSame('GBP', $currency);
+    }
+
+    public function testQuickObjectGetterRequiresCallable(): void
+    {
+        $this->expectException(\Exception::class);
+        (new QuickObject())->registerGetter('currency', 'nonexistent_function');
+    }
+
+    public function testQuickObjectReadOnlyProperty(): void
+    {
+        $obj = new QuickObject();
+        $obj->registerProperty('version', '1.0.0', ['writable' => false]);
+        $obj->registerProperty('secret', 'hidden', ['enumerable' => false]);
+        $this->js->registerObject('App', $obj);
+
+        $this->assertSame('1.0.0', $this->js->eval('App.version = "x"; App.version'));
+        $this->assertSame(['version'], $this->js->eval('Object.keys(App)'));
+        $this->assertSame('hidden', $this->js->eval('App.secret'));
+
+        $this->expectException(\Exception::class);
+        $this->js->eval('"use strict"; App.version = "x"');
+    }
+
+    public function testQuickObjectMemberAttributes(): void
+    {
+        $config = new QuickObject();
+        $config->registerProperty('debug', false);
+
+        $obj = new QuickObject();
+        $obj->registerFunction('upper', 'strtoupper', ['writable' => false, 'enumerable' => false]);
+        $obj->registerObject('config', $config, ['writable' => false, 'configurable' => false]);
+        $obj->registerGetter('now', fn () => 42, null, ['enumerable' => false]);
+        $obj->registerProperty('name', 'shop');
+        $this->js->registerObject('App', $obj);
+
+        $this->js->eval('App.upper = null; App.config = null; delete App.config');
+
+        $this->assertSame('HELLO', $this->js->eval('App.upper("hello")'));
+        $this->assertSame(['debug' => false], $this->js->eval('App.config'));
+        $this->assertSame(['config', 'name'], $this->js->eval('Object.keys(App)'));
+        $this->assertSame(42, $this->js->eval('App.now'));
+        $this->assertFalse($this->js->eval('Object.getOwnPropertyDescriptor(App, "config").configurable'));
+    }
+
+    public function testLiveQuickObjectMemberAttributes(): void
+    {
+        $obj = new QuickObject();
+        $obj->registerFunction('upper', 'strtoupper', ['writable' => false, 'configurable' => false]);
+        $obj->registerGetter('now', fn () => 42, null, ['enumerable' => false]);
+        $this->js->registerObject('App', $obj, ['live' => true]);
+
+        $this->js->eval('App.upper = null; delete App.upper');
+
+        $this->assertSame('HELLO', $this->js->eval('App.upper("hello")'));
+        $this->assertSame(['upper'], $this->js->eval('Object.keys(App)'));
+    }
+
+    public function testQuickObjectGetterRejectsWritableAttribute(): void
+    {
+        $this->expectExceptionMessage("Getter 'now' cannot have a writable attribute");
+        (new QuickObject())->registerGetter('now', fn () => 42, null, ['writable' => false]);
+    }
+
+    public function testFrozenQuickObjectCannotBeTamperedWith(): void
+    {
+        $obj = new QuickObject();
+        $obj->registerProperty('version', '1.0.0');
+        $obj->registerFunction('upper', 'strtoupper');
+        $obj->freeze();
+        $this->js->registerObject('App', $obj);
+
+        $this->js->eval('App.version = "x"; App.upper = null; App.extra = 1; delete App.version; App = null');
+
+        $this->assertSame('1.0.0', $this->js->eval('App.version'));
+        $this->assertSame('HELLO', $this->js->eval('App.upper("hello")'));
+        $this->assertTrue($this->js->eval('Object.isFrozen(App) && App.extra === undefined'));
+    }
+
+    public function testQuickObjectIsCopiedByDefault(): void
+    {
+        $obj = new QuickObject();
+        $obj->registerProperty('currency', 'EUR');
+        $this->js->registerObject('Shop', $obj);
+        $obj->registerProperty('currency', 'USD');
+
+        $this->assertSame('EUR', $this->js->eval('Shop.currency'));
+    }
+
+    public function testLiveQuickObjectReflectsPhpChanges(): void
+    {
+        $obj = new QuickObject();
+        $obj->registerProperty('currency', 'EUR');
+        $this->js->registerObject('Shop', $obj, ['live' => true]);
+
+        $obj->registerProperty('currency', 'USD');
+        $obj->registerFunction('upper', 'strtoupper');
+
+        $this->assertSame('USD', $this->js->eval('Shop.currency'));
+        $this->assertSame('HELLO', $this->js->eval('Shop.upper("hello")'));
+        $this->assertSame(['currency', 'upper'], $this->js->eval('Object.keys(Shop)'));
+    }
+
+    public function testLiveQuickObjectReceivesScriptWrites(): void
+    {
+        $obj = new QuickObject();
+        $obj->registerProperty('locked', 'yes', ['writable' => false]);
+        $this->js->registerObject('Shop', $obj, ['live' => true]);
+
+        $this->js->eval('Shop.items = 2; Shop.locked = "no"');
+
+        $this->assertSame(2, $this->js->eval('Shop.items'));
+        $this->assertSame('yes', $this->js->eval('Shop.locked'));
+        $this->assertTrue($this->js->eval('"items" in Shop'));
+    }
+
+    public function testLiveQuickObjectRejectsObjectWrites(): void
+    {
+        $this->js->registerObject('Shop', new QuickObject(), ['live' => true]);
+
+        // The array would be converted on assignment and the push lost
+        $this->assertSame(
+            'TypeError',
+            $this->js->eval('try { Shop.items = []; Shop.items.push(1); "assigned" } catch (e) { e.name }')
+        );
+        $this->assertFalse($this->js->eval('"items" in Shop'));
+    }
+
+    public function testLiveQuickObjectRejectsFunctionWrites(): void
+    {
+        $obj = new QuickObject();
+        $obj->registerFunction('upper', 'strtoupper');
+        $this->js->registerObject('Shop', $obj, ['live' => true]);
+
+        $this->assertSame('TypeError', $this->js->eval('try { Shop.format = () => "x"; "assigned" } catch (e) { e.name }'));
+        $this->assertSame('TypeError', $this->js->eval('try { Shop.upper = 1; "assigned" } catch (e) { e.name }'));
+        $this->assertFalse($this->js->eval('"format" in Shop'));
+        $this->assertSame('HELLO', $this->js->eval('Shop.upper("hello")'));
+    }
+
+    public function testLiveNestedQuickObject(): void
+    {
+        $config = new QuickObject();
+        $app = new QuickObject();
+        $app->registerObject('config', $config, ['live' => true]);
+        $this->js->registerObject('App', $app);
+
+        $config->registerProperty('debug', true);
+
+        $this->assertTrue($this->js->eval('App.config.debug'));
+    }
+
+    public function testLiveQuickObjectCannotBeNestedInsideItself(): void
+    {
+        $app = new QuickObject();
+        $config = new QuickObject();
+        $app->registerObject('config', $config, ['live' => true]);
+
+        try {
+            $app->registerObject('self', $app, ['live' => true]);
+            $this->fail('Expected exception for self-nesting');
+        } catch (\Exception $e) {
+            $this->assertStringContainsString('cannot be nested inside itself', $e->getMessage());
+        }
+
+        $this->expectException(\Exception::class);
+        $this->expectExceptionMessage('cannot be nested inside itself');
+        $config->registerObject('app', $app, ['live' => true]);
+    }
+
+    public function testCopiedQuickObjectCanContainItself(): void
+    {
+        $obj = new QuickObject();
+        $obj->registerProperty('name', 'outer');
+        $obj->registerObject('copy', $obj);
+        $this->js->registerObject('Obj', $obj);
+
+        $this->assertSame('outer', $this->js->eval('Obj.copy.name'));
+    }
+
+    public function testQuickObjectPropertiesConvertLikeSetGlobal(): void
+    {
+        $obj = new QuickObject();
+        $obj->registerProperty('tags', []);
+        $obj->registerProperty('created', new \DateTimeImmutable('2024-01-15 10:30:00', new \DateTimeZone('UTC')));
+        $obj->registerProperty('logo', new QuickBinary("\x89PNG"));
+        $this->js->registerObject('Shop', $obj);
+        $this->js->setGlobal('tags', []);
+
+        $this->assertTrue($this->js->eval('Array.isArray(Shop.tags) && Array.isArray(tags)'));
+        $this->assertSame('2024-01-15T10:30:00.000Z', $this->js->eval('Shop.created.toISOString()'));
+        $this->assertTrue($this->js->eval('Shop.logo instanceof Uint8Array'));
+    }
+
     public function testQuickObjectInvalidFunctionThrows(): void
     {
         $this->expectException(\Exception::class);
@@ -527,4 +1488,111 @@ class QuickJSTest extends TestCase
         $this->expectException(\Exception::class);
         $this->js->addObjectMethod('Obj', 'broken', 'nonexistent_function');
     }
+
+    public function testDeleteGlobal(): void
+    {
+        $this->js->setGlobal('request', ['id' => 42]);
+        $this->js->deleteGlobal('request');
+        $this->js->deleteGlobal('request');
+
+        $this->assertFalse($this->js->hasGlobal('request'));
+    }
+
+    public function testUnregisterFunction(): void
+    {
+        $this->js->registerFunction('upper', 'strtoupper');
+        $this->js->unregisterFunction('upper');
+
+        $this->assertSame('undefined', $this->js->typeofGlobal('upper'));
+    }
+
+    public function testUnregisterMissingFunctionThrows(): void
+    {
+        $this->js->setGlobal('upper', 'not a function');
+
+        $this->expectException(\Exception::class);
+        $this->expectExceptionMessage('Function not found: upper');
+        $this->js->unregisterFunction('upper');
+    }
+
+    public function testRemoveObjectMember(): void
+    {
+        $this->js->addObjectMethod('Utils', 'upper', 'strtoupper');
+        $this->js->addObjectProperty('Utils', 'version', '1.0.0');
+        $this->js->removeObjectMember('Utils', 'upper');
+
+        $this->assertSame(['version'], $this->js->eval('Object.keys(Utils)'));
+    }
+
+    public function testDeleteGlobalReleasesPhpCallables(): void
+    {
+        $tracker = new \stdClass();
+        $reference = \WeakReference::create($tracker);
+
+        $obj = new QuickObject();
+        $obj->registerGetter('tracker', fn () => $tracker);
+        $this->js->registerObject('Shop', $obj);
+        unset($obj, $tracker);
+
+        $this->assertNotNull($reference->get());
+        $this->js->deleteGlobal('Shop');
+        $this->js->gc();
+        $this->assertNull($reference->get());
+    }
+
+    public function testDeleteGlobalBeforeSnapshotReleasesPhpCallables(): void
+    {
+        $js = new QuickJS(['snapshots' => true]);
+        $tracker = new \stdClass();
+        $reference = \WeakReference::create($tracker);
+
+        $js->eval('var total = 1');
+        $obj = new QuickObject();
+        $obj->registerGetter('tracker', fn () => $tracker);
+        $js->registerObject('Shop', $obj);
+        unset($obj, $tracker);
+
+        $js->deleteGlobal('Shop');
+        $js->gc();
+        $this->assertNull($reference->get());
+
+        $js->snapshot();
+        $js->restore();
+        $this->assertFalse($js->hasGlobal('Shop'));
+        $this->assertSame(1, $js->eval('total'));
+    }
+
+    public function testDeleteGlobalKeepsObjectsScriptsReference(): void
+    {
+        $js = new QuickJS(['snapshots' => true]);
+        $js->addObjectProperty('Utils', 'version', '1.0.0');
+        $js->eval('var utils = Utils');
+        $js->addObjectMethod('Utils', 'upper', 'strtoupper');
+        $js->deleteGlobal('Utils');
+
+        $js->snapshot();
+        $js->restore();
+        $this->assertFalse($js->hasGlobal('Utils'));
+        $this->assertSame('HI', $js->eval("utils.upper('hi')"));
+    }
+
+    public function testDeleteGlobalAfterSnapshotKeepsCallablesForRestore(): void
+    {
+        $js = new QuickJS(['snapshots' => true]);
+        $tracker = new \stdClass();
+        $reference = \WeakReference::create($tracker);
+
+        $obj = new QuickObject();
+        $obj->registerGetter('tracker', fn () => $tracker);
+        $js->registerObject('Shop', $obj);
+        unset($obj, $tracker);
+
+        $js->snapshot();
+        $js->deleteGlobal('Shop');
+        $js->gc();
+        $this->assertNotNull($reference->get());
+
+        $js->restore();
+        $this->assertTrue($js->hasGlobal('Shop'));
+    }
 }
@@ -0,0 +1,228 @@
+<?php
+
+declare(strict_types=1);
+
+namespace Shopware\PHPExtension\Tests;
+
+use PHPUnit\Framework\TestCase;
+use Shopware\PHPExtension\LightningCSS\LightningCSS;
+use Shopware\PHPExtension\Scss\ScssCompiler;
+
+class ScssCompilerTest extends TestCase
+{
+    private ScssCompiler $scss;
+
+    protected function setUp(): void
+    {
+        $this->scss = new ScssCompiler();
+    }
+
+    public function testCanCreateInstance(): void
+    {
+        $this->assertInstanceOf(ScssCompiler::class, $this->scss);
+    }
+
+    public function testCompileNestedRules(): void
+    {
+        $result = $this->scss->compile('.card { .title { color: red; } }');
+
+        $this->assertStringContainsString('.card .title', $result);
+        $this->assertStringContainsString('color: red', $result);
+    }
+
+    public function testCompileCompressed(): void
+    {
+        $result = $this->scss->compile('$c: red; body { color: $c; }', ['style' => 'compressed']);
+
+        $this->assertSame('body{color:red}', trim($result));
+    }
+
+    public function testCompileWithUnknownStyleThrows(): void
+    {
+        $this->expectException(\Exception::class);
+
+        $this->scss->compile('body { color: red; }', ['style' => 'nested']);
+    }
+
+    public function testCompileInvalidScssThrows(): void
+    {
+        $this->expectException(\Exception::class);
+
+        $this->scss->compile('body { color: $undefined; }');
+    }
+
+    public function testSetVariables(): void
+    {
+        $this->scss->setVariables([
+            'primary' => '#008490',
+            '$radius' => '3px',
+            'shadows' => false,
+        ]);
+
+        $result = $this->scss->compile(
+            '.btn { color: $primary; border-radius: $radius; @if $shadows { box-shadow: none; } }',
+            ['style' => 'compressed']
+        );
+
+        $this->assertSame('.btn{color:#008490;border-radius:3px}', trim($result));
+    }
+
+    public function testSetVariablesWithIndentedSyntax(): void
+    {
+        $this->scss->setVariables(['primary' => '#008490']);
+
+        $result = $this->scss->compile(".btn\n  color: $primary\n", ['syntax' => 'sass', 'style' => 'compressed']);
+
+        $this->assertSame('.btn{color:#008490}', trim($result));
+    }
+
+    public function testSourceMapOptionThrows(): void
+    {
+        $this->expectException(\Exception::class);
+        $this->expectExceptionMessage('Source maps are not implemented yet');
+
+        $this->scss->compile('a { color: red; }', ['source_map' => true]);
+    }
+
+    public function testSetVariablesRejectsArrays(): void
+    {
+        $this->expectException(\Exception::class);
+
+        $this->scss->setVariables(['colors' => ['red', 'blue']]);
+    }
+
+    public function testImportPath(): void
+    {
+        $dir = sys_get_temp_dir() . '/scss-' . uniqid();
+        mkdir($dir);
+        file_put_contents($dir . '/_variables.scss', '$brand: blue;');
+
+        try {
+            $this->scss->addImportPath($dir);
+            $result = $this->scss->compile('@import "variables"; a { color: $brand; }', ['style' => 'compressed']);
+
+            $this->assertSame('a{color:blue}', trim($result));
+        } finally {
+            unlink($dir . '/_variables.scss');
+            rmdir($dir);
+        }
+    }
+
+    public function testCompileFileResolvesRelativeImports(): void
+    {
+        $dir = sys_get_temp_dir() . '/scss-' . uniqid();
+        mkdir($dir);
+        file_put_contents($dir . '/_mixins.scss', '@mixin red { color: red; }');
+        file_put_contents($dir . '/main.scss', '@import "mixins"; a { @include red; }');
+
+        try {
+            $this->scss->setVariables(['unused' => '1px']);
+            $result = $this->scss->compileFile($dir . '/main.scss', ['style' => 'compressed']);
+
+            $this->assertSame('a{color:red}', trim($result));
+        } finally {
+            unlink($dir . '/_mixins.scss');
+            unlink($dir . '/main.scss');
+            rmdir($dir);
+        }
+    }
+
+    public function testCustomImporter(): void
+    {
+        $files = ['plugin/theme' => '$plugin-color: green;'];
+        $this->scss->addImporter(fn (string $url) => $files[$url] ?? null);
+
+        $result = $this->scss->compile('@import "plugin/theme"; a { color: $plugin-color; }', ['style' => 'compressed']);
+
+        $this->assertSame('a{color:green}', trim($result));
+    }
+
+    public function testImporterReceivesEachImportUrlOnce(): void
+    {
+        $files = [
+            'plugin/theme' => '@import "mixins"; $plugin-color: green;',
+            'mixins' => '@mixin spaced { margin: 1px; }',
+        ];
+        $urls = [];
+        $this->scss->addImporter(function (string $url) use ($files, &$urls): ?string {
+            $urls[] = $url;
+
+            return $files[$url] ?? null;
+        });
+        $this->scss->setImportPaths([sys_get_temp_dir(), sys_get_temp_dir() . '/scss-missing']);
+
+        $result = $this->scss->compile(
+            '@use "sass:math"; @import "plugin/theme", "plugin/theme", "print.css"; a { color: $plugin-color; @include spaced; }',
+            ['style' => 'compressed']
+        );
+
+        $this->assertSame('@import "print.css";a{color:green;margin:1px}', trim($result));
+        $this->assertSame(['plugin/theme', 'mixins'], $urls);
+    }
+
+    public function testImporterIsAskedBeforeTheFileSystem(): void
+    {
+        $dir = sys_get_temp_dir() . '/scss-' . uniqid();
+        mkdir($dir);
+        file_put_contents($dir . '/_variables.scss', '$brand: blue; @import "nested";');
+        file_put_contents($dir . '/_nested.scss', '$accent: blue;');
+
+        try {
+            $this->scss->addImportPath($dir);
+            $this->scss->addImporter(fn (string $url) => $url === 'nested' ? '$accent: green;' : null);
+            $result = $this->scss->compile('@import "variables"; a { color: $brand; b: $accent; }', ['style' => 'compressed']);
+
+            $this->assertSame('a{color:blue;b:green}', trim($result));
+        } finally {
+            unlink($dir . '/_variables.scss');
+            unlink($dir . '/_nested.scss');
+            rmdir($dir);
+        }
+    }
+
+    public function testImporterErrorsAreReported(): void
+    {
+        $this->scss->addImporter(function (string $path): string {
+            throw new \RuntimeException('importer failed');
+        });
+
+        $this->expectException(\Exception::class);
+
+        $this->scss->compile('@import "missing";');
+    }
+
+    public function testAddImporterRejectsNonCallable(): void
+    {
+        $this->expectException(\Exception::class);
+
+        $this->scss->addImporter('not_a_function_that_exists');
+    }
+
+    public function testCompileAndMinify(): void
+    {
+        $css = new LightningCSS();
+        $css->setBrowserTargets(['safari' => 8]);
+
+        $result = $this->scss->compileAndMinify(
+            '/*! Theme */ $c: #ff0000; .a { color: $c; user-select: none; }',
+            $css
+        );
+
+        $this->assertStringStartsWith('/*! Theme */', $result);
+        $this->assertStringContainsString('color:red', $result);
+        $this->assertStringContainsString('-webkit-user-select:none', $result);
+    }
+
+    public function testCompileAndMinifyPassesCompileOptions(): void
+    {
+        $this->scss->setVariables(['c' => '#ff0000']);
+
+        $result = $this->scss->compileAndMinify(
+            "/*! Theme */\n.a\n  color: $c\n",
+            new LightningCSS(),
+            ['syntax' => 'sass', 'license_comments' => false]
+        );
+
+        $this->assertSame('.a{color:red}', $result);
+    }
+}